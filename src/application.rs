@@ -2,14 +2,23 @@
 //! as well as current typing test
 //! main structs App and TestState
 
+use crate::browser::Browser;
 use crate::config;
+use crate::database;
+use crate::langs;
 use crossterm::event::KeyEvent;
 
-use crate::handlers::{self, KeyHandler};
-use crate::painters::{draw_post, draw_settings, draw_test_and_update, Painter};
+use crate::handlers::{self, KeyHandler, Ticker};
+use crate::painters::{
+    draw_failed, draw_history, draw_post, draw_progress, draw_replay, draw_settings, draw_test,
+    Painter,
+};
+use crate::progress::Progress;
+use crate::replay::Replay;
 use crate::settings::Settings;
 use crate::typer::TestState;
 use crate::Term;
+use std::time::{Duration, Instant};
 
 pub const APPLOGO: &str = " _._ _  _ |  _    
 _>| | |(_)|<(/_\\/ 
@@ -18,10 +27,14 @@ _>| | |(_)|<(/_\\/
 pub struct App<'t> {
     pub settings: Settings,
     pub test: TestState<'t>,
+    pub replay: Replay<'t>,
+    pub browser: Browser,
+    pub progress: Progress,
     pub margin: u16,
     pub paragraph: u16,
     pub key_handler: KeyHandler,
     pub painter: Painter,
+    pub ticker: Ticker,
     pub is_alive: bool,
 }
 
@@ -61,22 +74,80 @@ impl App<'_> {
     /// assert!(!app.is_alive);
     /// ```
     pub fn handle_key_event(&mut self, key_event: KeyEvent) {
-        (self.key_handler)(key_event, self)
+        let now = self.test.clock.now();
+        self.handle_key_event_at(key_event, now)
+    }
+
+    /// handles a key event that was read at the given moment
+    /// the test times the key by it instead of by when it gets to it
+    pub fn handle_key_event_at(&mut self, key_event: KeyEvent, at: Instant) {
+        self.test.stamp = Some(at);
+        (self.key_handler)(key_event, self);
+        self.test.stamp = None;
+    }
+
+    /// Lets the current screen react to the passing time
+    /// returns how long the main loop may wait for the next key event
+    pub fn tick(&mut self) -> Duration {
+        (self.ticker)(self)
     }
 
     pub fn change_to_post(&mut self) {
         self.painter = draw_post;
         self.key_handler = handlers::post::handle;
+        self.ticker = handlers::idle;
+    }
+
+    /// failed runs get their own screen instead of the results
+    pub fn change_to_failed(&mut self) {
+        self.painter = draw_failed;
+        self.key_handler = handlers::post::handle;
+        self.ticker = handlers::idle;
+    }
+
+    pub fn change_to_replay(&mut self) {
+        self.painter = draw_replay;
+        self.key_handler = handlers::replay::handle;
+        self.ticker = handlers::replay::tick;
+    }
+
+    pub fn change_to_history(&mut self) {
+        self.painter = draw_history;
+        self.key_handler = handlers::history::handle;
+        self.ticker = handlers::idle;
+    }
+
+    /// reads the run history anew and shows it
+    pub fn open_history(&mut self) {
+        self.browser = Browser::load(&self.settings.database.conn);
+        self.change_to_history();
+    }
+
+    pub fn change_to_progress(&mut self) {
+        self.painter = draw_progress;
+        self.key_handler = handlers::progress::handle;
+        self.ticker = handlers::idle;
     }
 
+    /// charts every run of the current test, leaving the chart calls back
+    pub fn open_progress(&mut self, back: fn(&mut App)) {
+        let conn = &self.settings.database.conn;
+        self.progress = Progress::load(conn, &self.settings.test_cfg, back);
+        self.change_to_progress();
+    }
+
+    /// practice runs end once the settings are opened
     pub fn change_to_settings(&mut self) {
+        self.settings.end_practice();
         self.painter = draw_settings;
         self.key_handler = handlers::settings::handle;
+        self.ticker = handlers::idle;
     }
 
     pub fn change_to_test(&mut self) {
-        self.painter = draw_test_and_update;
+        self.painter = draw_test;
         self.key_handler = handlers::typer::handle;
+        self.ticker = handlers::typer::tick;
     }
 
     pub fn stop(&mut self) {
@@ -84,8 +155,102 @@ impl App<'_> {
     }
 
     pub fn reset_test(&mut self) {
-        self.test.cursor_x = 1;
+        self.settings.test_cfg.pick_text();
+        self.settings.load_lesson();
+        self.settings.load_drill();
         self.test.reset(&self.settings.test_cfg);
+        self.arm_test();
+    }
+
+    /// runs the same text again, the run is saved as a retry
+    pub fn retry_test(&mut self) {
+        self.test.retry(&self.settings.test_cfg);
+        self.arm_test();
+    }
+
+    /// replays the run that was just finished
+    pub fn replay_last_run(&mut self) {
+        let back: fn(&mut App) = if self.settings.test_cfg.test_summary.failed {
+            |app| app.change_to_failed()
+        } else {
+            |app| app.change_to_post()
+        };
+        self.replay = Replay::from_test(&self.test, &self.settings.test_cfg, back);
+        self.change_to_replay();
+    }
+
+    /// replays a run from the history, leaving the replay calls back
+    /// returns false if the run can't be replayed
+    pub fn replay_saved_run(&mut self, run_id: i64, back: fn(&mut App)) -> bool {
+        let conn = &self.settings.database.conn;
+        match database::keystrokes::get_recording(conn, run_id) {
+            Some(recording) => {
+                self.replay = Replay::from_recording(run_id, recording, self.test.colors, back);
+                self.change_to_replay();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// starts a test made only of the words that went badly in the last one
+    pub fn practice_weak_words(&mut self) {
+        if let Some(text) = self.settings.postbox.words.practice_text() {
+            self.settings
+                .start_practice(&text)
+                .expect("practice text isn't empty");
+            self.reset_test();
+            self.change_to_test();
+        }
+    }
+
+    /// lines up the carets the test is raced against
+    fn arm_test(&mut self) {
+        self.test.pace_wpm = self.settings.pace_wpm();
+        self.test.ghost = self.settings.ghost();
+        self.feed_test();
+    }
+
+    /// starts the test that comes after the finished one
+    /// failed parts of custom text have to be typed again
+    /// and practice runs give way to the test they interrupted
+    pub fn next_test(&mut self) {
+        if !self.settings.end_practice() && !self.settings.test_cfg.test_summary.failed {
+            self.settings.test_cfg.next_part();
+        }
+        self.reset_test();
+    }
+
+    /// keeps the word stream of timed tests flowing
+    pub fn feed_test(&mut self) {
+        if self.test.is_starving() {
+            self.test.refill(&self.settings.test_cfg);
+        }
+    }
+
+    /// summarizes the test, saves the results
+    /// and moves on to the post screen
+    pub fn finish_test(&mut self) {
+        let summary = self.test.summarize();
+        let failed = summary.failed;
+        let run_id = self.settings.save_test_results(summary);
+        let conn = &self.settings.database.conn;
+        database::keystrokes::save_keystrokes(
+            conn,
+            run_id,
+            &self.test.keystrokes,
+            &langs::lines_to_text(&self.test.pristine),
+            self.test.skip_indent,
+        )
+        .expect("saving the keystrokes");
+
+        if failed {
+            self.change_to_failed();
+        } else {
+            self.change_to_post();
+        }
+        self.settings.record_lesson_run();
+        self.settings.cache_run_stats(&self.test);
     }
 
     pub fn from_config() -> Self {
@@ -113,11 +278,15 @@ impl Default for App<'_> {
             margin: 2,
             paragraph: 62,
 
-            painter: draw_test_and_update,
+            painter: draw_test,
             key_handler: handlers::typer::handle,
+            ticker: handlers::typer::tick,
 
             settings: Settings::default(),
             test: TestState::default(),
+            replay: Replay::default(),
+            browser: Browser::default(),
+            progress: Progress::default(),
         }
     }
 }
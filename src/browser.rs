@@ -0,0 +1,259 @@
+//! the run history browser
+//! runs are read once when it's opened, sorting and filtering happen in memory
+
+use crate::database::history::{get_runs, RunEntry};
+use crate::settings::{TestMod, BITFLAG_MODS};
+use rusqlite::Connection;
+use std::cmp::Ordering;
+use std::fmt;
+use tui::widgets::TableState;
+
+/// the browser keeps this many of the latest runs
+const BROWSER_RUNS: usize = 10_000;
+/// rows skipped by page up and page down
+pub const PAGE: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Date,
+    Wpm,
+    Acc,
+}
+
+/// which mods the listed runs must have been typed with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModFilter {
+    Any,
+    NoMods,
+    With(TestMod),
+}
+
+impl ModFilter {
+    /// any, no mods and then every mod on its own
+    fn next(self) -> Self {
+        let after = match self {
+            Self::Any => return Self::NoMods,
+            Self::NoMods => 0,
+            Self::With(test_mod) => bitflag(test_mod),
+        };
+        BITFLAG_MODS
+            .iter()
+            .filter(|(&flag, _)| flag > after)
+            .min_by_key(|(&flag, _)| flag)
+            .map_or(Self::Any, |(_, &test_mod)| Self::With(test_mod))
+    }
+
+    fn matches(self, mods: u8) -> bool {
+        match self {
+            Self::Any => true,
+            Self::NoMods => mods == 0,
+            Self::With(test_mod) => mods & bitflag(test_mod) != 0,
+        }
+    }
+}
+
+fn bitflag(test_mod: TestMod) -> u8 {
+    *BITFLAG_MODS
+        .get_by_right(&test_mod)
+        .expect("BITFLAG_MODS should cover every mod")
+}
+
+impl fmt::Display for ModFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Any => write!(f, "any mods"),
+            Self::NoMods => write!(f, "no mods"),
+            Self::With(test_mod) => write!(f, "+ {}", test_mod),
+        }
+    }
+}
+
+pub struct Browser {
+    pub runs: Vec<RunEntry>,
+    /// indices of the runs passing the filters, in the sorted order
+    pub shown: Vec<usize>,
+    pub state: TableState,
+    pub sort: SortKey,
+    pub descending: bool,
+    /// runs of tests whose name contains it
+    pub name_filter: String,
+    /// typed keys go to the name filter
+    pub typing_filter: bool,
+    pub mod_filter: ModFilter,
+    /// the selected run is shown in full
+    pub details: bool,
+}
+
+impl Default for Browser {
+    fn default() -> Self {
+        Self {
+            runs: vec![],
+            shown: vec![],
+            state: TableState::default(),
+            sort: SortKey::Date,
+            descending: true,
+            name_filter: String::new(),
+            typing_filter: false,
+            mod_filter: ModFilter::Any,
+            details: false,
+        }
+    }
+}
+
+impl Browser {
+    pub fn load(conn: &Connection) -> Self {
+        Self::with_runs(get_runs(conn, BROWSER_RUNS).expect("reading the run history"))
+    }
+
+    fn with_runs(runs: Vec<RunEntry>) -> Self {
+        let mut browser = Self {
+            runs,
+            ..Self::default()
+        };
+        browser.refresh();
+        browser
+    }
+
+    /// filters and sorts the runs again, the selection goes back to the top
+    fn refresh(&mut self) {
+        let name = self.name_filter.to_lowercase();
+        let mod_filter = self.mod_filter;
+        self.shown = self
+            .runs
+            .iter()
+            .enumerate()
+            .filter(|(_, run)| run.test_name.to_lowercase().contains(&name))
+            .filter(|(_, run)| mod_filter.matches(run.mods))
+            .map(|(i, _)| i)
+            .collect();
+
+        let runs = &self.runs;
+        let key = |i: &usize| {
+            let run = &runs[*i];
+            match self.sort {
+                SortKey::Date => run.id as f64,
+                SortKey::Wpm => run.wpm,
+                SortKey::Acc => run.acc,
+            }
+        };
+        self.shown.sort_by(|a, b| {
+            let order = key(a).partial_cmp(&key(b)).unwrap_or(Ordering::Equal);
+            match self.descending {
+                true => order.reverse(),
+                false => order,
+            }
+        });
+
+        let selected = (!self.shown.is_empty()).then_some(0);
+        self.state.select(selected);
+    }
+
+    /// sorting by the same key again flips the order
+    pub fn sort_by(&mut self, key: SortKey) {
+        self.descending = match self.sort == key {
+            true => !self.descending,
+            false => true,
+        };
+        self.sort = key;
+        self.refresh();
+    }
+
+    pub fn next_mod_filter(&mut self) {
+        self.mod_filter = self.mod_filter.next();
+        self.refresh();
+    }
+
+    pub fn push_filter(&mut self, c: char) {
+        self.name_filter.push(c);
+        self.refresh();
+    }
+
+    pub fn pop_filter(&mut self) {
+        self.name_filter.pop();
+        self.refresh();
+    }
+
+    pub fn clear_filters(&mut self) {
+        self.name_filter.clear();
+        self.mod_filter = ModFilter::Any;
+        self.refresh();
+    }
+
+    /// moves the selection by the given number of rows, stopping at either end
+    pub fn scroll(&mut self, rows: isize) {
+        if let Some(selected) = self.state.selected() {
+            let last = self.shown.len() as isize - 1;
+            let moved = (selected as isize + rows).clamp(0, last);
+            self.state.select(Some(moved as usize));
+        }
+    }
+
+    pub fn first(&mut self) {
+        self.scroll(-(self.shown.len() as isize));
+    }
+
+    pub fn last(&mut self) {
+        self.scroll(self.shown.len() as isize);
+    }
+
+    pub fn selected(&self) -> Option<&RunEntry> {
+        let i = self.shown.get(self.state.selected()?)?;
+        self.runs.get(*i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(id: i64, test_name: &str, wpm: f64, acc: f64, mods: u8) -> RunEntry {
+        RunEntry {
+            id,
+            test_name: test_name.to_string(),
+            wpm,
+            acc,
+            mods,
+            ..RunEntry::default()
+        }
+    }
+
+    fn shown_ids(browser: &Browser) -> Vec<i64> {
+        browser.shown.iter().map(|&i| browser.runs[i].id).collect()
+    }
+
+    #[test]
+    fn test_browser() {
+        let mut browser = Browser::with_runs(vec![
+            run(3, "english", 80., 96., 0),
+            run(2, "polish", 95., 91., 0b01),
+            run(1, "english", 70., 99., 0b11),
+        ]);
+        assert_eq!(shown_ids(&browser), vec![3, 2, 1]);
+
+        browser.sort_by(SortKey::Wpm);
+        assert_eq!(shown_ids(&browser), vec![2, 3, 1]);
+        browser.sort_by(SortKey::Wpm);
+        assert_eq!(shown_ids(&browser), vec![1, 3, 2]);
+        browser.sort_by(SortKey::Acc);
+        assert_eq!(shown_ids(&browser), vec![1, 3, 2]);
+
+        browser.push_filter('E');
+        browser.push_filter('n');
+        assert_eq!(shown_ids(&browser), vec![1, 3]);
+        browser.next_mod_filter();
+        assert_eq!(browser.mod_filter, ModFilter::NoMods);
+        assert_eq!(shown_ids(&browser), vec![3]);
+        browser.next_mod_filter();
+        assert_eq!(browser.mod_filter, ModFilter::With(TestMod::Punctuation));
+        assert_eq!(shown_ids(&browser), vec![1]);
+
+        browser.clear_filters();
+        browser.scroll(5);
+        assert_eq!(browser.selected().map(|run| run.id), Some(2));
+        browser.scroll(-1);
+        assert_eq!(browser.selected().map(|run| run.id), Some(3));
+
+        browser.push_filter('x');
+        assert!(browser.selected().is_none());
+    }
+}
@@ -6,6 +6,7 @@ use serde_derive::Deserialize;
 use std::fs;
 
 use test_parser::UserTest;
+pub use test_parser::{try_parse_mods, TestOverride};
 use theme_parser::UserTheme;
 
 /// Default Config overwritten partially or completely
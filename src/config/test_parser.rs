@@ -1,12 +1,62 @@
-use crate::settings::{is_script, TestMod, TestVariant, TypingTestConfig, TEST_MODS};
+use crate::settings::{
+    is_code, is_quote, is_script, FailPolicy, Pace, TestMod, TestVariant, TypingTestConfig,
+    BACKSPACE_POLICIES, CODE_SIGN, ERROR_POLICIES, FAIL_POLICIES, IDLE_POLICIES, QUOTE_SIGN,
+    SCRIPT_SIGN, TEST_MODS,
+};
 use std::collections::HashSet;
 
 #[derive(serde_derive::Deserialize, Debug)]
 pub struct UserTest {
     name: Option<String>,
     len: Option<usize>,
+    // seconds, turns the test into a timed one
+    time: Option<u64>,
     pool: Option<usize>,
+    // starts the letter unlock lesson of the word list
+    lesson: Option<bool>,
+    // n-grams to drill, an empty list drills the weakest ones
+    drill: Option<Vec<String>>,
     mods: Option<Vec<String>>,
+    // "skip" or "type", indentation of code tests
+    indent: Option<String>,
+    // "free", "stop on letter" or "stop on word"
+    errors: Option<String>,
+    // "on", "within word" or "off"
+    backspace: Option<String>,
+    // "off", "sudden death" or "min accuracy"
+    fail: Option<String>,
+    // percent, implies the min accuracy fail policy
+    min_acc: Option<u8>,
+    // "pause", "abort" or "off", what happens when idle
+    idle: Option<String>,
+    // seconds without a key press that count as idle
+    idle_secs: Option<u64>,
+    // wpm of the pace caret, "average", "pb" or "off"
+    pace: Option<UserPace>,
+    // races the best run of the test
+    ghost: Option<bool>,
+}
+
+#[derive(serde_derive::Deserialize, Debug)]
+#[serde(untagged)]
+enum UserPace {
+    Wpm(u16),
+    Named(String),
+}
+
+impl UserPace {
+    fn into_pace(self) -> Option<Pace> {
+        match self {
+            Self::Wpm(0) => None,
+            Self::Wpm(wpm) => Some(Pace::Wpm(wpm)),
+            Self::Named(name) => match name.trim() {
+                "off" => Some(Pace::Off),
+                "average" => Some(Pace::Average),
+                "pb" => Some(Pace::Best),
+                _ => None,
+            },
+        }
+    }
 }
 
 impl UserTest {
@@ -15,7 +65,7 @@ impl UserTest {
         let name = self.name.unwrap_or_else(|| "english".to_string());
         let variant = resolve_test_variant(&name);
         let mut ttc = TypingTestConfig {
-            name,
+            name: strip_sign(&name, variant).to_string(),
             variant,
             ..TypingTestConfig::default()
         };
@@ -26,6 +76,24 @@ impl UserTest {
             }
         }
 
+        if let Some(duration) = self.time {
+            if duration > 0 && ttc.variant.uses_word_list() {
+                ttc.variant = TestVariant::Timed;
+                ttc.duration = duration
+            }
+        }
+
+        if self.lesson == Some(true) && ttc.variant.uses_word_list() {
+            ttc.variant = TestVariant::Lesson;
+        }
+
+        if let Some(drill) = self.drill {
+            if ttc.variant.uses_word_list() {
+                ttc.variant = TestVariant::Drill;
+                ttc.ngrams = parse_ngrams(&drill);
+            }
+        }
+
         if let Some(word_pool) = self.pool {
             if word_pool > 0 {
                 ttc.word_pool = word_pool
@@ -36,10 +104,119 @@ impl UserTest {
             ttc.mods = parse_mods(&mods)
         }
 
+        if let Some(indent) = self.indent {
+            ttc.skip_indent = indent.trim().to_lowercase() != "type"
+        }
+
+        if let Some(errors) = self.errors {
+            if let Some(&policy) = ERROR_POLICIES.get_by_left(errors.trim()) {
+                ttc.error_policy = policy
+            }
+        }
+
+        if let Some(backspace) = self.backspace {
+            if let Some(&policy) = BACKSPACE_POLICIES.get_by_left(backspace.trim()) {
+                ttc.backspace = policy
+            }
+        }
+
+        if let Some(pace) = self.pace.and_then(UserPace::into_pace) {
+            ttc.pace = pace
+        }
+
+        if let Some(ghost) = self.ghost {
+            ttc.ghost = ghost
+        }
+
+        if let Some(min_acc) = self.min_acc {
+            if min_acc <= 100 {
+                ttc.fail_policy = FailPolicy::MinAccuracy;
+                ttc.min_acc = min_acc
+            }
+        }
+
+        if let Some(fail) = self.fail {
+            if let Some(&policy) = FAIL_POLICIES.get_by_left(fail.trim()) {
+                ttc.fail_policy = policy
+            }
+        }
+
+        if let Some(idle) = self.idle {
+            if let Some(&policy) = IDLE_POLICIES.get_by_left(idle.trim()) {
+                ttc.idle_policy = policy
+            }
+        }
+
+        if let Some(idle_secs) = self.idle_secs {
+            if idle_secs > 0 {
+                ttc.idle_secs = idle_secs
+            }
+        }
+
         ttc
     }
 }
 
+/// parts of the test identity given on the command line
+/// the rest of the configured test stays as it is
+#[derive(Debug, Default)]
+pub struct TestOverride {
+    pub name: Option<String>,
+    pub len: Option<usize>,
+    // seconds, turns the test into a timed one
+    pub time: Option<u64>,
+    pub pool: Option<usize>,
+    pub mods: Option<HashSet<TestMod>>,
+}
+
+impl TestOverride {
+    pub fn apply(self, ttc: &mut TypingTestConfig) {
+        if let Some(name) = self.name {
+            ttc.variant = resolve_test_variant(&name);
+            ttc.name = strip_sign(&name, ttc.variant).to_string();
+        }
+
+        if let Some(length) = self.len.filter(|&length| length > 0) {
+            if ttc.variant.uses_word_list() {
+                ttc.variant = TestVariant::Standard;
+                ttc.length = length
+            }
+        }
+
+        if let Some(duration) = self.time.filter(|&duration| duration > 0) {
+            if ttc.variant.uses_word_list() {
+                ttc.variant = TestVariant::Timed;
+                ttc.duration = duration
+            }
+        }
+
+        if let Some(word_pool) = self.pool.filter(|&word_pool| word_pool > 0) {
+            ttc.word_pool = word_pool
+        }
+
+        if let Some(mods) = self.mods {
+            ttc.mods = mods
+        }
+    }
+}
+
+/// mods given on the command line, none stands for no mods
+/// unlike the config an unknown mod is an error
+pub fn try_parse_mods(raw_mods: &[String]) -> anyhow::Result<HashSet<TestMod>> {
+    let mut parsed_mods = HashSet::new();
+    for raw_mod in raw_mods.iter().map(|raw_mod| raw_mod.trim()) {
+        match TEST_MODS.get_by_left(raw_mod) {
+            Some(&parsed_mod) => {
+                parsed_mods.insert(parsed_mod);
+            }
+            None if raw_mod == "none" => {}
+            None => anyhow::bail!("unknown mod: {}", raw_mod),
+        }
+    }
+    Ok(parsed_mods)
+}
+
+/// unknown mods are left out
 fn parse_mods(raw_mods: &[String]) -> HashSet<TestMod> {
     let mut parsed_mods = HashSet::new();
     for raw_mod in raw_mods {
@@ -50,21 +227,174 @@ fn parse_mods(raw_mods: &[String]) -> HashSet<TestMod> {
     parsed_mods
 }
 
+/// accepts "th, ing" as well as separate strings
+fn parse_ngrams(raw_ngrams: &[String]) -> Vec<String> {
+    raw_ngrams
+        .iter()
+        .flat_map(|raw| raw.split(','))
+        .map(|ngram| ngram.trim().to_lowercase())
+        .filter(|ngram| !ngram.is_empty())
+        .collect()
+}
+
 fn resolve_test_variant(test_name: &str) -> TestVariant {
     if is_script(test_name) {
         TestVariant::Script
+    } else if is_quote(test_name) {
+        TestVariant::Quote
+    } else if is_code(test_name) {
+        TestVariant::Code
     } else {
         TestVariant::Standard
     }
 }
 
+/// name of the file the test comes from
+fn strip_sign(test_name: &str, variant: TestVariant) -> &str {
+    match variant {
+        TestVariant::Script => &test_name[SCRIPT_SIGN.len()..],
+        TestVariant::Quote => &test_name[QUOTE_SIGN.len()..],
+        TestVariant::Code => &test_name[CODE_SIGN.len()..],
+        _ => test_name,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::settings::TestMod;
+    use crate::settings::{BackspacePolicy, ErrorPolicy, IdlePolicy, TestMod};
     use crate::vec_of_strings;
     use std::collections::HashSet;
 
+    #[test]
+    fn test_timed_test_from_config() {
+        let user_test: UserTest = toml::from_str("time = 60").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.variant, TestVariant::Timed);
+        assert_eq!(ttc.duration, 60);
+
+        let user_test: UserTest = toml::from_str("name = \"#!gibberish\"\ntime = 60").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.variant, TestVariant::Script);
+        assert_eq!(ttc.name, "gibberish");
+    }
+
+    #[test]
+    fn test_code_test_from_config() {
+        let user_test: UserTest = toml::from_str("name = \"{}rust\"\nindent = \"type\"").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.variant, TestVariant::Code);
+        assert_eq!(ttc.name, "rust");
+        assert!(!ttc.skip_indent);
+    }
+
+    #[test]
+    fn test_drill_from_config() {
+        let user_test: UserTest = toml::from_str("drill = [\"th, ING\", \"ion\"]").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.variant, TestVariant::Drill);
+        assert_eq!(ttc.ngrams, vec!["th", "ing", "ion"]);
+
+        let user_test: UserTest = toml::from_str("drill = []").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.variant, TestVariant::Drill);
+        assert!(ttc.ngrams.is_empty());
+    }
+
+    #[test]
+    fn test_error_policy_from_config() {
+        let user_test: UserTest = toml::from_str("errors = \"stop on word\"").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.error_policy, ErrorPolicy::StopOnWord);
+
+        let user_test: UserTest = toml::from_str("errors = \"whatever\"").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.error_policy, ErrorPolicy::Free);
+
+        let user_test: UserTest = toml::from_str("backspace = \"within word\"").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.backspace, BackspacePolicy::WithinWord);
+    }
+
+    #[test]
+    fn test_fail_policy_from_config() {
+        let user_test: UserTest = toml::from_str("min_acc = 95").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.fail_policy, FailPolicy::MinAccuracy);
+        assert_eq!(ttc.min_acc, 95);
+
+        let user_test: UserTest = toml::from_str("fail = \"sudden death\"").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.fail_policy, FailPolicy::SuddenDeath);
+    }
+
+    #[test]
+    fn test_idle_policy_from_config() {
+        let user_test: UserTest = toml::from_str("idle = \"abort\"\nidle_secs = 5").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.idle_policy, IdlePolicy::Abort);
+        assert_eq!(ttc.idle_secs, 5);
+
+        let user_test: UserTest = toml::from_str("idle_secs = 0").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.idle_policy, IdlePolicy::Pause);
+        assert_eq!(ttc.idle_secs, 10);
+    }
+
+    #[test]
+    fn test_pace_from_config() {
+        let user_test: UserTest = toml::from_str("pace = 85").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.pace, Pace::Wpm(85));
+
+        let user_test: UserTest = toml::from_str("pace = \"pb\"").unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.pace, Pace::Best);
+    }
+
+    #[test]
+    fn test_override_test() {
+        let user_test: UserTest = toml::from_str("time = 30\nerrors = \"stop on word\"").unwrap();
+        let mut ttc = user_test.into_typing_test_config();
+        TestOverride {
+            len: Some(50),
+            mods: Some(HashSet::from([TestMod::Numbers])),
+            ..TestOverride::default()
+        }
+        .apply(&mut ttc);
+        assert_eq!(ttc.variant, TestVariant::Standard);
+        assert_eq!(ttc.length, 50);
+        assert_eq!(ttc.mods, HashSet::from([TestMod::Numbers]));
+        assert_eq!(ttc.error_policy, ErrorPolicy::StopOnWord);
+
+        TestOverride {
+            name: Some("\"\"english".to_string()),
+            ..TestOverride::default()
+        }
+        .apply(&mut ttc);
+        assert_eq!(ttc.variant, TestVariant::Quote);
+        assert_eq!(ttc.name, "english");
+    }
+
+    #[test]
+    fn test_try_parse_mods() {
+        let given = vec_of_strings!["numbers", " symbols"];
+        let mods = try_parse_mods(&given).unwrap();
+        assert_eq!(mods, HashSet::from([TestMod::Numbers, TestMod::Symbols]));
+        let none = vec_of_strings!["none"];
+        assert!(try_parse_mods(&none).unwrap().is_empty());
+        let typo = vec_of_strings!["numbrs"];
+        assert!(try_parse_mods(&typo).is_err());
+    }
+
+    #[test]
+    fn test_quote_test_from_config() {
+        let user_test: UserTest = toml::from_str(r#"name = '""english'"#).unwrap();
+        let ttc = user_test.into_typing_test_config();
+        assert_eq!(ttc.variant, TestVariant::Quote);
+        assert_eq!(ttc.name, "english");
+    }
+
     #[test]
     fn test_parse_mods() {
         let faulty = vec_of_strings!["nonexitant", "punctuation"];
@@ -0,0 +1,355 @@
+//! totals of the saved runs of every test, smokey stats prints them
+
+use super::history::SavedTest;
+use crate::settings::{BackspacePolicy, ErrorPolicy};
+use rusqlite::{self, params_from_iter, Connection, ToSql};
+use std::collections::HashMap;
+use std::io::{self, BufWriter, Write};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const WEEK: i64 = 7 * 86_400;
+/// percentiles of the accuracy
+const ACC_PERCENTILES: [f64; 3] = [10., 50., 90.];
+
+const STATS_STATEMENT: &str = r#"SELECT
+
+test.test_name,
+variant,
+length,
+word_pool,
+mods,
+duration,
+text_id,
+error_policy,
+backspace,
+wpm,
+acc,
+correct_chars,
+date
+
+FROM run
+INNER JOIN test ON test.test_id = run.test_id
+WHERE failed = 0"#;
+
+/// narrows down the runs that are counted
+#[derive(Debug, Default)]
+pub struct StatsFilter {
+    /// tests whose name contains it
+    pub name: Option<String>,
+    pub mods: Option<u8>,
+    /// runs of the last days only
+    pub days: Option<u32>,
+}
+
+/// everything smokey stats knows about a single test
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct TestStats {
+    pub label: String,
+    pub runs: usize,
+    /// seconds, pauses left out
+    pub time: f64,
+    pub mean_wpm: f64,
+    pub median_wpm: f64,
+    pub best_wpm: f64,
+    /// see ACC_PERCENTILES
+    pub acc: [f64; 3],
+    /// mean wpm of the last 7 days minus the one of the 7 days before
+    pub week_delta: Option<f64>,
+    /// date of the newest run, the latest typed tests go first
+    last: i64,
+}
+
+struct SavedRun {
+    wpm: f64,
+    acc: f64,
+    correct_chars: usize,
+    date: i64,
+}
+
+impl TestStats {
+    fn from_runs(label: String, runs: &[SavedRun], now: i64) -> Self {
+        let mut wpms: Vec<f64> = runs.iter().map(|run| run.wpm).collect();
+        wpms.sort_by(f64::total_cmp);
+        let mut accs: Vec<f64> = runs.iter().map(|run| run.acc).collect();
+        accs.sort_by(f64::total_cmp);
+
+        // the wpm only counts correct chars, so it gives back the time typed
+        let time = runs
+            .iter()
+            .filter(|run| run.wpm > 0.)
+            .map(|run| 12. * run.correct_chars as f64 / run.wpm)
+            .sum();
+
+        let mean_wpm_between = |from: i64, to: i64| {
+            let wpms: Vec<f64> = runs
+                .iter()
+                .filter(|run| run.date > from && run.date <= to)
+                .map(|run| run.wpm)
+                .collect();
+            (!wpms.is_empty()).then(|| wpms.iter().sum::<f64>() / wpms.len() as f64)
+        };
+        let this_week = mean_wpm_between(now - WEEK, now);
+        let last_week = mean_wpm_between(now - 2 * WEEK, now - WEEK);
+
+        Self {
+            label,
+            runs: runs.len(),
+            time,
+            mean_wpm: wpms.iter().sum::<f64>() / wpms.len() as f64,
+            median_wpm: percentile(&wpms, 50.),
+            best_wpm: wpms[wpms.len() - 1],
+            acc: ACC_PERCENTILES.map(|p| percentile(&accs, p)),
+            week_delta: this_week.zip(last_week).map(|(this, last)| this - last),
+            last: runs.iter().map(|run| run.date).max().unwrap_or_default(),
+        }
+    }
+}
+
+/// linear interpolation between the closest ranks, sorted has to be sorted
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = p / 100. * (sorted.len() - 1) as f64;
+    let (below, above) = (sorted[rank.floor() as usize], sorted[rank.ceil() as usize]);
+    below + (above - below) * rank.fract()
+}
+
+/// 1h 05m or 3m 20s
+fn format_time(secs: f64) -> String {
+    let secs = secs.round() as u64;
+    match secs / 3600 {
+        0 => format!("{}m {:02}s", secs / 60, secs % 60),
+        hours => format!("{}h {:02}m", hours, secs / 60 % 60),
+    }
+}
+
+pub struct Stats {
+    pub tests: Vec<TestStats>,
+}
+
+impl Stats {
+    pub fn print(&self) {
+        let stdout = io::stdout();
+        let lock = stdout.lock();
+        let mut buff = BufWriter::new(lock);
+
+        let name_len = self
+            .tests
+            .iter()
+            .map(|test| test.label.len())
+            .max()
+            .unwrap_or_default()
+            .max("test".len());
+
+        writeln!(
+            buff,
+            "{:name_len$}|{:5}|{:8}|{:6}|{:6}|{:6}|{:17}|7d",
+            "test",
+            "runs",
+            "time",
+            "mean",
+            "median",
+            "best",
+            "acc p10/p50/p90",
+            name_len = name_len
+        )
+        .expect("couldn't write to stdout");
+        for test in &self.tests {
+            let week_delta = test
+                .week_delta
+                .map_or("-".to_string(), |delta| format!("{:+.2}", delta));
+            writeln!(
+                buff,
+                "{:name_len$}|{:<5}|{:8}|{:6}|{:6}|{:6}|{:17}|{}",
+                test.label,
+                test.runs,
+                format_time(test.time),
+                format!("{:.2}", test.mean_wpm),
+                format!("{:.2}", test.median_wpm),
+                format!("{:.2}", test.best_wpm),
+                format!("{:.0}/{:.0}/{:.0}", test.acc[0], test.acc[1], test.acc[2]),
+                week_delta,
+                name_len = name_len
+            )
+            .expect("oof: couldn't write to stdout")
+        }
+        buff.flush().expect("oof: couldn't flush to stdout");
+    }
+}
+
+pub fn get_stats(conn: &Connection, filter: &StatsFilter) -> Result<Stats, rusqlite::Error> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs() as i64);
+    get_stats_at(conn, filter, now)
+}
+
+/// stats of every test that passes the filter, failed runs are left out
+fn get_stats_at(
+    conn: &Connection,
+    filter: &StatsFilter,
+    now: i64,
+) -> Result<Stats, rusqlite::Error> {
+    let mut statement = STATS_STATEMENT.to_string();
+    let mut params: Vec<Box<dyn ToSql>> = vec![];
+    if let Some(name) = &filter.name {
+        // instr, since like would take % and _ in the name as wildcards
+        statement.push_str(" AND instr(lower(test.test_name), lower(?)) > 0");
+        params.push(Box::new(name.clone()));
+    }
+    if let Some(mods) = filter.mods {
+        statement.push_str(" AND mods = ?");
+        params.push(Box::new(mods));
+    }
+    if let Some(days) = filter.days {
+        statement.push_str(" AND date > ?");
+        params.push(Box::new(now - days as i64 * 86_400));
+    }
+
+    let mut stmt = conn.prepare(&statement)?;
+    let rows = stmt.query_map(params_from_iter(params), |row| {
+        let test = SavedTest {
+            name: row.get(0)?,
+            variant: row.get(1)?,
+            length: row.get(2)?,
+            word_pool: row.get(3)?,
+            mods: row.get(4)?,
+            duration: row.get(5)?,
+            text_id: row.get(6)?,
+            error_policy: row.get(7)?,
+            backspace: row.get(8)?,
+        };
+        let run = SavedRun {
+            wpm: row.get(9)?,
+            acc: row.get(10)?,
+            correct_chars: row.get(11)?,
+            date: row.get(12)?,
+        };
+        Ok((test, run))
+    })?;
+
+    let mut runs_of: HashMap<SavedTest, Vec<SavedRun>> = HashMap::new();
+    for row in rows {
+        let (mut test, run) = row?;
+        // practice texts differ from run to run, they are summed up together
+        if test.is_practice() {
+            test.text_id = 0;
+        }
+        runs_of.entry(test).or_default().push(run);
+    }
+
+    let mut tests: Vec<TestStats> = runs_of
+        .iter()
+        .map(|(test, runs)| TestStats::from_runs(label(test), runs, now))
+        .collect();
+    tests.sort_by_key(|test| std::cmp::Reverse(test.last));
+    Ok(Stats { tests })
+}
+
+/// tests with stricter rules keep their own stats, the label tells them apart
+fn label(test: &SavedTest) -> String {
+    let mut label = test.label();
+    if test.error_policy > 0 {
+        let policy = ErrorPolicy::from_discriminant(test.error_policy);
+        label.push_str(&format!(" ({})", policy));
+    }
+    if test.backspace > 0 {
+        let policy = BackspacePolicy::from_discriminant(test.backspace);
+        label.push_str(&format!(" (backspace {})", policy));
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{init, RunHistoryDatbase};
+    use crate::settings::{TestMod, TypingTestConfig};
+
+    #[test]
+    fn test_percentile() {
+        let sorted = [10., 20., 30., 40.];
+        assert!((percentile(&sorted, 50.) - 25.).abs() < f64::EPSILON);
+        assert!((percentile(&sorted, 0.) - 10.).abs() < f64::EPSILON);
+        assert!((percentile(&sorted, 100.) - 40.).abs() < f64::EPSILON);
+        assert!((percentile(&[7.], 90.) - 7.).abs() < f64::EPSILON);
+        assert_eq!(format_time(200.), "3m 20s");
+        assert_eq!(format_time(3900.), "1h 05m");
+    }
+
+    #[test]
+    fn test_get_stats() {
+        let mut db = RunHistoryDatbase {
+            conn: Connection::open_in_memory().unwrap(),
+        };
+        init::init_db(&mut db.conn).unwrap();
+        let mut ttc = TypingTestConfig::default();
+        for (wpm, acc) in [(60., 90.), (80., 100.), (100., 95.)] {
+            ttc.test_summary.wpm = wpm;
+            ttc.test_summary.acc = acc;
+            ttc.test_summary.correct_chars = 100;
+            db.save(&ttc);
+        }
+        ttc.test_summary.failed = true;
+        db.save(&ttc);
+        ttc.test_summary.failed = false;
+        ttc.error_policy = ErrorPolicy::StopOnWord;
+        db.save(&ttc);
+        ttc.mods.insert(TestMod::Numbers);
+        db.save(&ttc);
+
+        // the first two runs fall in the week before, the rest of them in this one
+        let conn = &db.conn;
+        let now: i64 = conn
+            .query_row("SELECT strftime('%s', 'now')", [], |row| row.get(0))
+            .map(|now: String| now.parse().unwrap())
+            .unwrap();
+        conn.execute(
+            "UPDATE run SET date = date - ? WHERE run_id < 3",
+            [WEEK + 1],
+        )
+        .unwrap();
+
+        let stats = get_stats_at(conn, &StatsFilter::default(), now).unwrap();
+        assert_eq!(stats.tests.len(), 3);
+        let english = stats.tests.iter().find(|test| test.runs == 3).unwrap();
+        assert_eq!(english.runs, 3);
+        assert!((english.mean_wpm - 80.).abs() < 1e-9);
+        assert!((english.median_wpm - 80.).abs() < 1e-9);
+        assert!((english.best_wpm - 100.).abs() < 1e-9);
+        assert!((english.acc[1] - 95.).abs() < 1e-9);
+        // 100 correct chars are 20 words
+        assert!((english.time - (20. + 15. + 12.)).abs() < 1e-9);
+        assert!((english.week_delta.unwrap() - 30.).abs() < 1e-9);
+
+        let filter = StatsFilter {
+            mods: Some(0b10),
+            ..StatsFilter::default()
+        };
+        let stats = get_stats_at(conn, &filter, now).unwrap();
+        assert_eq!(stats.tests.len(), 1);
+        assert!(stats.tests[0].label.ends_with("(stop on word)"));
+
+        let filter = StatsFilter {
+            name: Some("pol".to_string()),
+            ..StatsFilter::default()
+        };
+        assert!(get_stats_at(conn, &filter, now).unwrap().tests.is_empty());
+
+        let filter = StatsFilter {
+            name: Some("ENG".to_string()),
+            ..StatsFilter::default()
+        };
+        assert_eq!(get_stats_at(conn, &filter, now).unwrap().tests.len(), 3);
+        let filter = StatsFilter {
+            name: Some("e%h".to_string()),
+            ..StatsFilter::default()
+        };
+        assert!(get_stats_at(conn, &filter, now).unwrap().tests.is_empty());
+
+        let filter = StatsFilter {
+            days: Some(7),
+            ..StatsFilter::default()
+        };
+        let stats = get_stats_at(conn, &filter, now).unwrap();
+        assert_eq!(stats.tests.iter().map(|test| test.runs).sum::<usize>(), 3);
+    }
+}
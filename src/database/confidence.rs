@@ -0,0 +1,53 @@
+//! confidence in every letter, used by the weak letters mod
+//! unlike the other tables it's a running tally rather than a log of runs
+use crate::stats::LetterConfidence;
+use rusqlite::{params, Connection, Result as SqlResult};
+
+pub fn get_confidence(conn: &Connection) -> SqlResult<LetterConfidence> {
+    let mut stmt = conn.prepare("SELECT letter, confidence FROM confidence")?;
+    let rows = stmt.query_map([], |row| {
+        let letter: String = row.get(0)?;
+        let confidence: f64 = row.get(1)?;
+        Ok((letter, confidence))
+    })?;
+
+    let mut confidence = LetterConfidence::default();
+    for row in rows {
+        let (letter, value) = row?;
+        if let Some(letter) = letter.chars().next() {
+            confidence.set(letter, value);
+        }
+    }
+    Ok(confidence)
+}
+
+pub fn save_confidence(conn: &Connection, confidence: &LetterConfidence) -> SqlResult<()> {
+    let mut stmt =
+        conn.prepare("INSERT OR REPLACE INTO confidence (letter, confidence) VALUES (?, ?)")?;
+    for (letter, value) in confidence.iter() {
+        stmt.execute(params![letter.to_string(), value])?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::init;
+
+    #[test]
+    fn test_save_and_get_confidence() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init::init_db(&mut conn).unwrap();
+        assert_eq!(get_confidence(&conn).unwrap(), LetterConfidence::default());
+
+        let mut confidence = LetterConfidence::default();
+        confidence.set('a', 0.9);
+        confidence.set('ż', 0.25);
+        save_confidence(&conn, &confidence).unwrap();
+
+        confidence.set('a', 0.5);
+        save_confidence(&conn, &confidence).unwrap();
+        assert_eq!(get_confidence(&conn).unwrap(), confidence);
+    }
+}
@@ -0,0 +1,55 @@
+//! the best run of a test races the player as a ghost
+//! ghosts are replayed from the keystrokes of the run, see Replay::into_timeline
+use super::{same_test_params, SAME_TEST};
+use crate::settings::TypingTestConfig;
+use rusqlite::{params_from_iter, Connection, OptionalExtension};
+
+/// id of the fastest run of the same test that can be replayed
+pub fn get_ghost_run(conn: &Connection, ttc: &TypingTestConfig) -> Option<i64> {
+    conn.query_row(
+        &format!(
+            "SELECT run.run_id FROM run
+            INNER JOIN keystrokes ON keystrokes.run_id = run.run_id
+            WHERE {} AND keystrokes.text != ''
+            ORDER BY wpm DESC
+            LIMIT 1",
+            SAME_TEST
+        ),
+        params_from_iter(same_test_params(ttc)),
+        |row| row.get(0),
+    )
+    .optional()
+    .ok()?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{init, keystrokes::save_keystrokes, RunHistoryDatbase};
+
+    #[test]
+    fn test_get_fastest_ghost() {
+        let mut db = RunHistoryDatbase {
+            conn: Connection::open_in_memory().unwrap(),
+        };
+        init::init_db(&mut db.conn).unwrap();
+        let mut ttc = TypingTestConfig::default();
+        assert!(get_ghost_run(&db.conn, &ttc).is_none());
+
+        let mut fastest = 0;
+        for (wpm, text) in [(60., "a"), (90., "b"), (70., "c"), (120., "")] {
+            ttc.test_summary.wpm = wpm;
+            let run_id = db.save(&ttc);
+            save_keystrokes(&db.conn, run_id, &[], text, true).unwrap();
+            if wpm == 90. {
+                fastest = run_id;
+            }
+        }
+        // runs without their text can't be replayed
+        assert_eq!(get_ghost_run(&db.conn, &ttc), Some(fastest));
+
+        // other tests have their own ghosts
+        ttc.length = 100;
+        assert!(get_ghost_run(&db.conn, &ttc).is_none());
+    }
+}
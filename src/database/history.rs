@@ -1,4 +1,4 @@
-use crate::settings;
+use crate::settings::{self, BackspacePolicy, ErrorPolicy, TestVariant};
 use rusqlite::{self, Connection};
 use std::io::{self, BufWriter, Write};
 
@@ -12,7 +12,22 @@ correct_chars,
 mistakes,
 datetime(date, 'unixepoch', 'localtime'),
 length,
-word_pool
+word_pool,
+duration,
+variant,
+text_id,
+failed,
+retries,
+run_id,
+raw_wpm,
+consistency,
+corrected,
+cpm,
+paused,
+wall_wpm,
+error_policy,
+backspace,
+EXISTS(SELECT 1 FROM keystrokes WHERE keystrokes.run_id = run.run_id AND keystrokes.text != '')
 
 FROM run
 INNER JOIN test ON test.test_id = run.test_id
@@ -37,8 +52,68 @@ fn decode(bitflag: u8) -> String {
     result
 }
 
+/// the columns telling the tests of saved runs apart
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct SavedTest {
+    pub name: String,
+    pub variant: u8,
+    pub length: usize,
+    pub word_pool: usize,
+    pub mods: u8,
+    pub duration: u64,
+    pub text_id: usize,
+    pub error_policy: u8,
+    pub backspace: u8,
+}
+
+impl SavedTest {
+    /// made of the weak words of a run, see App::practice_weak_words
+    pub fn is_practice(&self) -> bool {
+        self.variant == TestVariant::Text as u8 && self.name == settings::PRACTICE
+    }
+
+    /// test name along with its length, word pool and mods
+    pub fn label(&self) -> String {
+        let name = &self.name;
+        if self.variant == TestVariant::Quote as u8 {
+            format!("{} #{}", name, self.text_id)
+        } else if self.is_practice() {
+            // text_id is a hash of the practice text, nothing to read from it
+            name.clone()
+        } else if self.variant == TestVariant::Text as u8 {
+            format!("{} part {}", name, self.text_id + 1)
+        } else if self.variant == TestVariant::Lesson as u8 {
+            format!("{} lesson {}", name, self.text_id)
+        } else if self.variant == TestVariant::Drill as u8 {
+            format!(
+                "{} drill {}/{}{}",
+                name,
+                self.length,
+                self.word_pool,
+                decode(self.mods)
+            )
+        } else if self.word_pool == 0 {
+            name.clone()
+        } else {
+            let length = if self.duration > 0 {
+                format!("{}s", self.duration)
+            } else {
+                self.length.to_string()
+            };
+
+            format!(
+                "{} {}/{}{}",
+                name,
+                length,
+                self.word_pool,
+                decode(self.mods)
+            )
+        }
+    }
+}
+
 pub struct History {
-    som: Vec<EntryCell>,
+    som: Vec<RunEntry>,
     justing: JustingInfo,
 }
 
@@ -50,28 +125,40 @@ impl History {
 
         let clen = format!("{}", self.justing.correct).len();
 
+        let idlen = format!("{}", self.justing.id).len();
+
         writeln!(
             buff,
-            "{:6}|{:6}|{:clen$}|{:3}",
+            "{:idlen$}|{:6}|{:6}|{:6}|{:6}|{:clen$}|{:3}|{:3}",
+            "id",
             "wpm",
+            "raw",
             "acc",
+            "con",
             "c",
             "mis",
-            clen = clen
+            "fix",
+            clen = clen,
+            idlen = idlen
         )
         .expect("couldn't write to stdout");
         for a in &self.som {
             writeln!(
                 buff,
-                "{:6}|{:6}|{:<w$}|{:<3}|{:width$}|{}",
+                "{:<idlen$}|{:6}|{:6}|{:6}|{:6}|{:<w$}|{:<3}|{:<3}|{:width$}|{}",
+                a.id,
                 format!("{:.2}", a.wpm),
+                format!("{:.2}", a.raw),
                 format!("{:.2}", a.acc),
+                format!("{:.2}", a.consistency),
                 a.correct,
                 a.mis,
-                a.name,
+                a.fix,
+                a.label,
                 a.date,
                 width = self.justing.name_len,
                 w = clen,
+                idlen = idlen,
             )
             .expect("oof: couldn't write to stdout")
         }
@@ -79,70 +166,114 @@ impl History {
     }
 }
 
-#[derive(Debug)]
-struct EntryCell {
-    wpm: f64,
-    acc: f64,
-    correct: usize,
-    mis: usize,
-    name: String,
-    date: String,
+/// a saved run as the history shows it
+#[derive(Debug, Default, Clone)]
+pub struct RunEntry {
+    pub id: i64,
+    pub wpm: f64,
+    pub raw: f64,
+    pub cpm: f64,
+    pub acc: f64,
+    pub consistency: f64,
+    // seconds the clock was stopped for
+    pub paused: f64,
+    pub wall_wpm: f64,
+    pub correct: usize,
+    // uncorrected mistakes
+    pub mis: usize,
+    // corrected ones
+    pub fix: usize,
+    pub test_name: String,
+    // test name along with its length, word pool and mods
+    pub label: String,
+    pub mods: u8,
+    pub error_policy: ErrorPolicy,
+    pub backspace: BackspacePolicy,
+    pub failed: bool,
+    pub retries: u32,
+    // the keystrokes were saved along with the run
+    pub replayable: bool,
+    pub date: String,
 }
 
 #[derive(Debug, Default)]
 struct JustingInfo {
     name_len: usize,
     correct: usize,
+    id: i64,
 }
 
 impl JustingInfo {
-    fn update(&mut self, entry_cell: &EntryCell) {
-        if entry_cell.name.len() > self.name_len {
-            self.name_len = entry_cell.name.len()
+    fn update(&mut self, entry_cell: &RunEntry) {
+        if entry_cell.label.len() > self.name_len {
+            self.name_len = entry_cell.label.len()
         }
-        self.correct = std::cmp::max(self.correct, entry_cell.correct)
+        self.correct = std::cmp::max(self.correct, entry_cell.correct);
+        self.id = std::cmp::max(self.id, entry_cell.id)
     }
 }
 
 pub fn get_history(conn: &Connection, limit: usize) -> Result<History, rusqlite::Error> {
-    let mut stmt = conn.prepare(CLI_HISTORY_STATEMENT)?;
     let mut justing = JustingInfo::default();
+    let runs = get_runs(conn, limit)?;
+    for run in &runs {
+        justing.update(run);
+    }
 
-    let rows = stmt.query_map([limit], |row| {
-        let word_pool: usize = row.get(8)?;
+    Ok(History { som: runs, justing })
+}
 
-        let name: String = if word_pool == 0 {
-            row.get(2)?
-        } else {
-            let raw_name: String = row.get(2)?;
-            let length: usize = row.get(7)?;
-            format!(
-                "{} {}/{}{}",
-                raw_name,
-                length,
-                word_pool,
-                decode(row.get(3)?)
-            )
+/// the latest runs, newest first
+pub fn get_runs(conn: &Connection, limit: usize) -> Result<Vec<RunEntry>, rusqlite::Error> {
+    let mut stmt = conn.prepare(CLI_HISTORY_STATEMENT)?;
+
+    let rows = stmt.query_map([limit], |row| {
+        let test = SavedTest {
+            name: row.get(2)?,
+            variant: row.get(10)?,
+            length: row.get(7)?,
+            word_pool: row.get(8)?,
+            mods: row.get(3)?,
+            duration: row.get(9)?,
+            text_id: row.get(11)?,
+            error_policy: row.get(21)?,
+            backspace: row.get(22)?,
         };
+        let mut label = test.label();
 
-        let s = EntryCell {
+        let failed: bool = row.get(12)?;
+        if failed {
+            label.push_str(" (failed)");
+        }
+
+        let retries: u32 = row.get(13)?;
+        if retries > 0 {
+            label.push_str(&format!(" (retry {})", retries));
+        }
+
+        Ok(RunEntry {
+            id: row.get(14)?,
             wpm: row.get(0)?,
+            raw: row.get(15)?,
+            cpm: row.get(18)?,
             acc: row.get(1)?,
+            consistency: row.get(16)?,
+            paused: row.get(19)?,
+            wall_wpm: row.get(20)?,
             correct: row.get(4)?,
             mis: row.get(5)?,
-            name,
+            fix: row.get(17)?,
+            label,
+            mods: test.mods,
+            error_policy: ErrorPolicy::from_discriminant(test.error_policy),
+            backspace: BackspacePolicy::from_discriminant(test.backspace),
+            test_name: test.name,
+            failed,
+            retries,
+            replayable: row.get(23)?,
             date: row.get(6)?,
-        };
-
-        justing.update(&s);
-        Ok(s)
+        })
     })?;
 
-    // It would oof earlier than this unwrap anyway right? xD
-    let container: Vec<EntryCell> = rows.map(|x| x.unwrap()).collect();
-
-    Ok(History {
-        som: container,
-        justing,
-    })
+    rows.collect()
 }
@@ -1,13 +1,6 @@
-use crate::storage;
-use rusqlite::Connection;
+use crate::settings::TestVariant;
 use rusqlite::Result as SqlResult;
-
-// TODO this shouldn't exist btw
-pub fn debug_init_db() {
-    if let Ok(mut conn) = Connection::open(&*storage::DATABASE) {
-        init_db(&mut conn).unwrap()
-    }
-}
+use rusqlite::{params, Connection};
 
 // TODO move to build???
 pub fn init_db(conn: &mut Connection) -> SqlResult<()> {
@@ -15,6 +8,9 @@ pub fn init_db(conn: &mut Connection) -> SqlResult<()> {
 
     test_table_init(&tx)?;
     run_table_init(&tx)?;
+    keystrokes_table_init(&tx)?;
+    confidence_table_init(&tx)?;
+    lesson_table_init(&tx)?;
 
     tx.commit()?;
 
@@ -45,6 +41,69 @@ fn run_table_init(conn: &Connection) -> SqlResult<()> {
     wpm REAL NOT NULL,
     acc REAL NOT NULL,
     mods INTEGER NOT NULL,
+    duration INTEGER NOT NULL DEFAULT 0,
+    variant INTEGER NOT NULL DEFAULT 0,
+    text_id INTEGER NOT NULL DEFAULT 0,
+    error_policy INTEGER NOT NULL DEFAULT 0,
+    failed INTEGER NOT NULL DEFAULT 0,
+    backspace INTEGER NOT NULL DEFAULT 0,
+    retries INTEGER NOT NULL DEFAULT 0,
+    corrected INTEGER NOT NULL DEFAULT 0,
+    raw_wpm REAL NOT NULL DEFAULT 0,
+    cpm REAL NOT NULL DEFAULT 0,
+    consistency REAL NOT NULL DEFAULT 0,
+    paused REAL NOT NULL DEFAULT 0,
+    wall_wpm REAL NOT NULL DEFAULT 0,
+    FOREIGN KEY (test_id) REFERENCES test (test_id) ON DELETE CASCADE
+    );",
+        [],
+    )?;
+    migrate_run_table(conn)?;
+    Ok(())
+}
+
+/// every key press of runs and the text they were typed on
+/// see database::keystrokes
+fn keystrokes_table_init(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS keystrokes (
+    run_id INTEGER PRIMARY KEY,
+    log BLOB NOT NULL,
+    text TEXT NOT NULL DEFAULT '',
+    skip_indent INTEGER NOT NULL DEFAULT 1,
+    FOREIGN KEY (run_id) REFERENCES run (run_id) ON DELETE CASCADE
+    );",
+        [],
+    )?;
+    add_column_if_missing(conn, "keystrokes", "text", "TEXT NOT NULL DEFAULT ''")?;
+    add_column_if_missing(
+        conn,
+        "keystrokes",
+        "skip_indent",
+        "INTEGER NOT NULL DEFAULT 1",
+    )?;
+    Ok(())
+}
+
+/// running confidence in every letter, see database::confidence
+fn confidence_table_init(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS confidence (
+    letter TEXT PRIMARY KEY,
+    confidence REAL NOT NULL
+    );",
+        [],
+    )?;
+    Ok(())
+}
+
+/// progress of the lesson of every word list, see database::lesson
+fn lesson_table_init(conn: &Connection) -> SqlResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS lesson (
+    test_id INTEGER PRIMARY KEY,
+    unlocked INTEGER NOT NULL,
+    streak INTEGER NOT NULL DEFAULT 0,
     FOREIGN KEY (test_id) REFERENCES test (test_id) ON DELETE CASCADE
     );",
         [],
@@ -52,6 +111,54 @@ fn run_table_init(conn: &Connection) -> SqlResult<()> {
     Ok(())
 }
 
+/// brings run tables created by older versions up to date
+fn migrate_run_table(conn: &Connection) -> SqlResult<()> {
+    add_column_if_missing(conn, "run", "duration", "INTEGER NOT NULL DEFAULT 0")?;
+
+    if add_column_if_missing(conn, "run", "variant", "INTEGER NOT NULL DEFAULT 0")? {
+        // before variants were saved scripts were told apart by the lack of word pool
+        conn.execute(
+            "UPDATE run SET variant = ? WHERE word_pool = 0",
+            [TestVariant::Script as u8],
+        )?;
+    }
+
+    add_column_if_missing(conn, "run", "text_id", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "error_policy", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "failed", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "backspace", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "retries", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "corrected", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "raw_wpm", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "cpm", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "consistency", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "paused", "REAL NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "run", "wall_wpm", "REAL NOT NULL DEFAULT 0")?;
+    Ok(())
+}
+
+/// returns true if the column had to be added
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> SqlResult<bool> {
+    let count: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM pragma_table_info(?) WHERE name = ?",
+        params![table, column],
+        |row| row.get(0),
+    )?;
+
+    if count == 0 {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(count == 0)
+}
+
 pub fn enable_foreign_keys(conn: &Connection) {
     conn.execute("PRAGMA foreign_keys = ON", []).unwrap();
 }
@@ -101,4 +208,53 @@ mod tests {
             .query_row("SELECT date from run LIMIT 1", [], |row| row.get(0))
             .unwrap();
     }
+
+    #[test]
+    fn test_migrate_old_run_table() {
+        let mut conn = connect();
+        conn.execute(
+            "CREATE TABLE run (
+            run_id INTEGER PRIMARY KEY,
+            date INTEGER NOT NULL,
+            test_id INTEGER NOT NULL,
+            length INTEGER NOT NULL,
+            word_pool INTEGER NOT NULL,
+            correct_chars INTEGER NOT NULL,
+            mistakes INTEGER NOT NULL,
+            wpm REAL NOT NULL,
+            acc REAL NOT NULL,
+            mods INTEGER NOT NULL
+            );",
+            [],
+        )
+        .unwrap();
+        conn.execute(
+            "INSERT INTO run (date, test_id, length, mods, word_pool, correct_chars, mistakes, wpm, acc)
+            VALUES (0, 1, 25, 0, 5000, 120, 0, 80., 97.5), (0, 2, 0, 0, 0, 120, 0, 80., 97.5);",
+            [],
+        )
+        .unwrap();
+
+        init_db(&mut conn).expect("migration went oof");
+        // running it again doesn't hurt
+        init_db(&mut conn).expect("second migration went oof");
+
+        let duration: i64 = conn
+            .query_row("SELECT duration from run LIMIT 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(duration, 0);
+
+        // old script runs are recognized
+        let variants: Vec<u8> = conn
+            .prepare("SELECT variant from run ORDER BY run_id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .map(|v| v.unwrap())
+            .collect();
+        assert_eq!(
+            variants,
+            vec![TestVariant::Standard as u8, TestVariant::Script as u8]
+        );
+    }
 }
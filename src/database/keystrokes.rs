@@ -0,0 +1,204 @@
+//! every key press of past runs
+//! the log is packed into varints, a keystroke of ascii text typed
+//! at a sane pace takes about 4 bytes
+//! the text of the run is kept alongside so it can be replayed
+use crate::settings::ErrorPolicy;
+use crate::typer::Keystroke;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqlResult};
+
+/// everything needed to replay a run
+#[derive(Debug)]
+pub struct Recording {
+    pub keystrokes: Vec<Keystroke>,
+    /// lines of the test flattened by langs::lines_to_text
+    pub text: String,
+    pub skip_indent: bool,
+    pub error_policy: ErrorPolicy,
+    pub name: String,
+    pub wpm: f64,
+}
+
+/// 7 bits at a time, the highest bit tells if more bytes follow
+fn push_varint(blob: &mut Vec<u8>, mut n: u64) {
+    while n >= 0x80 {
+        blob.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    blob.push(n as u8);
+}
+
+fn read_varint(bytes: &mut impl Iterator<Item = u8>) -> Option<u64> {
+    let mut n = 0;
+    for shift in (0..64).step_by(7) {
+        let byte = bytes.next()?;
+        n |= ((byte & 0x7f) as u64) << shift;
+        if byte < 0x80 {
+            return Some(n);
+        }
+    }
+    None
+}
+
+/// each keystroke is the ms passed since the previous one
+/// with the correction flag in the lowest bit, then both chars
+fn encode(keystrokes: &[Keystroke]) -> Vec<u8> {
+    let mut blob = Vec::with_capacity(keystrokes.len() * 4);
+    let mut last = 0;
+    for key in keystrokes {
+        let delta = key.ms.saturating_sub(last) as u64;
+        last = key.ms;
+        push_varint(&mut blob, delta << 1 | key.correction as u64);
+        push_varint(&mut blob, key.expected as u64);
+        push_varint(&mut blob, key.typed as u64);
+    }
+    blob
+}
+
+fn decode(blob: &[u8]) -> Vec<Keystroke> {
+    let mut bytes = blob.iter().copied();
+    let mut keystrokes = vec![];
+    let mut ms = 0;
+
+    let to_char = |n: u64| char::from_u32(n as u32).unwrap_or(char::REPLACEMENT_CHARACTER);
+
+    while let Some(head) = read_varint(&mut bytes) {
+        let (Some(expected), Some(typed)) = (read_varint(&mut bytes), read_varint(&mut bytes))
+        else {
+            break;
+        };
+        ms += (head >> 1) as u32;
+        keystrokes.push(Keystroke {
+            ms,
+            expected: to_char(expected),
+            typed: to_char(typed),
+            correction: head & 1 == 1,
+        });
+    }
+    keystrokes
+}
+
+pub fn save_keystrokes(
+    conn: &Connection,
+    run_id: i64,
+    keystrokes: &[Keystroke],
+    text: &str,
+    skip_indent: bool,
+) -> SqlResult<()> {
+    conn.execute(
+        "INSERT INTO keystrokes (run_id, log, text, skip_indent) VALUES (?, ?, ?, ?);",
+        params![run_id, encode(keystrokes), text, skip_indent],
+    )?;
+    Ok(())
+}
+
+/// None for runs saved before keystrokes were logged
+pub fn get_keystrokes(conn: &Connection, run_id: i64) -> Option<Vec<Keystroke>> {
+    let blob: Option<Vec<u8>> = conn
+        .query_row(
+            "SELECT log FROM keystrokes WHERE run_id = ?",
+            [run_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .ok()?;
+
+    blob.map(|blob| decode(&blob))
+}
+
+/// logs of the most recent runs, newest first
+pub fn get_recent_keystrokes(conn: &Connection, runs: usize) -> SqlResult<Vec<Vec<Keystroke>>> {
+    let mut stmt = conn.prepare("SELECT log FROM keystrokes ORDER BY run_id DESC LIMIT ?")?;
+    let logs = stmt.query_map([runs], |row| {
+        let blob: Vec<u8> = row.get(0)?;
+        Ok(decode(&blob))
+    })?;
+    logs.collect()
+}
+
+/// None if the run can't be replayed
+pub fn get_recording(conn: &Connection, run_id: i64) -> Option<Recording> {
+    conn.query_row(
+        "SELECT keystrokes.log, keystrokes.text, keystrokes.skip_indent,
+        run.error_policy, test.test_name, run.wpm
+        FROM keystrokes
+        INNER JOIN run ON run.run_id = keystrokes.run_id
+        INNER JOIN test ON test.test_id = run.test_id
+        WHERE keystrokes.run_id = ? AND keystrokes.text != ''",
+        [run_id],
+        |row| {
+            let blob: Vec<u8> = row.get(0)?;
+            Ok(Recording {
+                keystrokes: decode(&blob),
+                text: row.get(1)?,
+                skip_indent: row.get(2)?,
+                error_policy: ErrorPolicy::from_discriminant(row.get(3)?),
+                name: row.get(4)?,
+                wpm: row.get(5)?,
+            })
+        },
+    )
+    .optional()
+    .ok()?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::{init, RunHistoryDatbase};
+    use crate::settings::TypingTestConfig;
+    use crate::typer::BACKSPACE;
+
+    fn key(ms: u32, expected: char, typed: char) -> Keystroke {
+        Keystroke {
+            ms,
+            expected,
+            typed,
+            correction: typed == BACKSPACE,
+        }
+    }
+
+    #[test]
+    fn test_encode_decode() {
+        let keystrokes = vec![
+            key(0, 'a', 'a'),
+            key(120, 'b', 'v'),
+            key(300, 'c', BACKSPACE),
+            key(90_000, 'ź', 'ź'),
+        ];
+        let blob = encode(&keystrokes);
+        assert_eq!(decode(&blob), keystrokes);
+        // the ascii keystrokes typed at a regular pace are small
+        assert!(encode(&keystrokes[..3]).len() <= 12);
+
+        // a truncated blob doesn't yield garbage
+        assert_eq!(decode(&blob[..blob.len() - 1]), keystrokes[..3]);
+    }
+
+    #[test]
+    fn test_save_and_get_keystrokes() {
+        let mut db = RunHistoryDatbase {
+            conn: Connection::open_in_memory().unwrap(),
+        };
+        init::init_db(&mut db.conn).unwrap();
+
+        let run_id = db.save(&TypingTestConfig::default());
+        assert!(get_keystrokes(&db.conn, run_id).is_none());
+
+        let keystrokes = vec![key(0, 'a', 'a'), key(150, 'b', BACKSPACE)];
+        save_keystrokes(&db.conn, run_id, &keystrokes, "ab", false).unwrap();
+        assert_eq!(get_keystrokes(&db.conn, run_id), Some(keystrokes.clone()));
+
+        let other_run = db.save(&TypingTestConfig::default());
+        save_keystrokes(&db.conn, other_run, &[], "", true).unwrap();
+        assert_eq!(
+            get_recent_keystrokes(&db.conn, 5).unwrap(),
+            vec![vec![], keystrokes.clone()]
+        );
+
+        let recording = get_recording(&db.conn, run_id).unwrap();
+        assert_eq!(recording.keystrokes, keystrokes);
+        assert_eq!(recording.text, "ab");
+        assert!(!recording.skip_indent);
+        assert_eq!(recording.name, TypingTestConfig::default().name);
+    }
+}
@@ -0,0 +1,53 @@
+//! how far the lessons of each word list got
+use super::{get_test_id, get_test_id_or_create};
+use crate::langs::lesson::Lesson;
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// unlocked letters and the streak, None for lessons that weren't started
+pub fn get_lesson_progress(conn: &Connection, name: &str) -> Option<(usize, u32)> {
+    let test_id = get_test_id(conn, name).ok()?;
+    conn.query_row(
+        "SELECT unlocked, streak FROM lesson WHERE test_id = ?",
+        [test_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .ok()?
+}
+
+pub fn save_lesson_progress(conn: &Connection, lesson: &Lesson) -> anyhow::Result<()> {
+    let test_id = get_test_id_or_create(conn, &lesson.name)?;
+    conn.execute(
+        "INSERT OR REPLACE INTO lesson (test_id, unlocked, streak) VALUES (?, ?, ?)",
+        params![test_id, lesson.unlocked, lesson.streak],
+    )?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::init;
+
+    #[test]
+    fn test_save_and_get_lesson_progress() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        init::init_db(&mut conn).unwrap();
+        assert_eq!(get_lesson_progress(&conn, "english"), None);
+
+        let mut lesson = Lesson {
+            name: "english".to_string(),
+            letters: "etaoinsr".chars().collect(),
+            unlocked: 6,
+            streak: 1,
+        };
+        save_lesson_progress(&conn, &lesson).unwrap();
+        assert_eq!(get_lesson_progress(&conn, "english"), Some((6, 1)));
+
+        lesson.unlocked = 7;
+        lesson.streak = 0;
+        save_lesson_progress(&conn, &lesson).unwrap();
+        assert_eq!(get_lesson_progress(&conn, "english"), Some((7, 0)));
+        assert_eq!(get_lesson_progress(&conn, "polish"), None);
+    }
+}
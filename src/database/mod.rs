@@ -1,9 +1,14 @@
+pub mod aggregate;
+pub mod confidence;
+pub mod ghost;
 pub mod history;
 pub mod init;
+pub mod keystrokes;
+pub mod lesson;
 use crate::settings::{TestMod, TestVariant, TypingTestConfig, BITFLAG_MODS};
 use crate::storage;
 use anyhow::Result;
-use rusqlite::{params, Connection};
+use rusqlite::{params, params_from_iter, Connection, ToSql};
 use std::collections::HashSet;
 
 /// A wrapper around rusqlite::Connection
@@ -15,39 +20,32 @@ pub struct RunHistoryDatbase {
 
 impl Default for RunHistoryDatbase {
     fn default() -> Self {
-        Self {
-            conn: Connection::open(&*storage::DATABASE).expect("couldn't open db"),
-        }
+        Self::with_connection(Connection::open(&*storage::DATABASE).expect("couldn't open db"))
     }
 }
 
 impl RunHistoryDatbase {
-    pub fn save(&mut self, ttc: &TypingTestConfig) {
+    /// databases written by older versions are brought up to date first
+    pub fn with_connection(mut conn: Connection) -> Self {
+        init::init_db(&mut conn).expect("couldn't init db");
+        Self { conn }
+    }
+
+    /// returns the id of the saved run
+    pub fn save(&mut self, ttc: &TypingTestConfig) -> i64 {
         let test_id = get_test_id_or_create(&self.conn, &ttc.name).unwrap();
         let sum = &ttc.test_summary;
-
-        let (len, mods, pool): (usize, u8, usize);
-
-        match ttc.variant {
-            TestVariant::Script => {
-                len = 0;
-                mods = 0;
-                pool = 0;
-            }
-            TestVariant::Standard => {
-                len = ttc.length;
-                mods = encode_test_mod_bitflag(&ttc.mods);
-                pool = ttc.word_pool;
-            }
-        }
+        let tid = ttc.gib_identity();
 
         self.conn.execute(
-            "INSERT INTO run (date, test_id, length, mods, word_pool, correct_chars, mistakes, wpm, acc)
-            VALUES ((SELECT strftime('%s', 'now')), ?, ?, ?, ?, ?, ?, ?, ?);",
-            params![test_id, len, mods, pool,
-            sum.correct_chars, sum.mistakes, sum.wpm, sum.acc],
+            "INSERT INTO run (date, test_id, variant, length, mods, word_pool, duration, text_id, error_policy, backspace, failed, retries, correct_chars, mistakes, corrected, wpm, raw_wpm, cpm, consistency, paused, wall_wpm, acc)
+            VALUES ((SELECT strftime('%s', 'now')), ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?);",
+            params![test_id, tid.variant as u8, tid.length, tid.mods, tid.word_pool, tid.duration, tid.text_id,
+            tid.error_policy as u8, tid.backspace as u8, sum.failed, sum.retries, sum.correct_chars, sum.mistakes, sum.corrected,
+            sum.wpm, sum.raw_wpm, sum.cpm, sum.consistency, sum.paused, sum.wall_wpm, sum.acc],
             )
             .expect("inserting into run");
+        self.conn.last_insert_rowid()
     }
 
     pub fn print_history(&self, limit: usize) {
@@ -55,36 +53,115 @@ impl RunHistoryDatbase {
             .expect("could")
             .print();
     }
+
+    pub fn print_stats(&self, filter: &aggregate::StatsFilter) {
+        aggregate::get_stats(&self.conn, filter)
+            .expect("reading the stats")
+            .print();
+    }
 }
 
-pub fn get_max_wpm_script(conn: &Connection, script_name: &str) -> Option<f64> {
+pub fn get_max_wpm_script(conn: &Connection, ttc: &TypingTestConfig) -> Option<f64> {
     conn.query_row(
         "SELECT max(wpm) FROM run WHERE
-        test_id = (select test_id FROM test WHERE test_name = ?)",
-        params![&script_name,],
+        test_id = (select test_id FROM test WHERE test_name = ?)
+        AND variant = ?
+        AND error_policy = ?
+        AND backspace = ?
+        AND failed = 0",
+        params![
+            &ttc.name,
+            TestVariant::Script as u8,
+            ttc.error_policy as u8,
+            ttc.backspace as u8
+        ],
         |row| row.get(0),
     )
     .ok()
 }
 
-pub fn get_max_wpm(conn: &Connection, ttc: &TypingTestConfig) -> Option<f64> {
-    conn.query_row(
-        "SELECT max(wpm) FROM run WHERE
-        test_id = (select test_id FROM test WHERE test_name = ?)
+/// WHERE clause matching the runs of the same test as ttc
+/// bound by same_test_params
+const SAME_TEST: &str = "test_id = (select test_id FROM test WHERE test_name = ?)
         AND length = ?
         AND word_pool = ?
-        AND mods = ?",
-        params![
-            &ttc.name,
-            ttc.length,
-            ttc.word_pool,
-            encode_test_mod_bitflag(&ttc.mods),
-        ],
+        AND mods = ?
+        AND duration = ?
+        AND variant = ?
+        AND text_id = ?
+        AND error_policy = ?
+        AND backspace = ?
+        AND failed = 0";
+
+fn same_test_params(ttc: &TypingTestConfig) -> Vec<Box<dyn ToSql>> {
+    let tid = ttc.gib_identity();
+    vec![
+        Box::new(ttc.name.clone()),
+        Box::new(tid.length),
+        Box::new(tid.word_pool),
+        Box::new(tid.mods),
+        Box::new(tid.duration),
+        Box::new(tid.variant as u8),
+        Box::new(tid.text_id),
+        Box::new(tid.error_policy as u8),
+        Box::new(tid.backspace as u8),
+    ]
+}
+
+pub fn get_max_wpm(conn: &Connection, ttc: &TypingTestConfig) -> Option<f64> {
+    conn.query_row(
+        &format!("SELECT max(wpm) FROM run WHERE {}", SAME_TEST),
+        params_from_iter(same_test_params(ttc)),
         |row| row.get(0),
     )
     .ok()
 }
 
+/// average wpm of the last few runs of the test
+pub fn get_average_wpm(conn: &Connection, ttc: &TypingTestConfig, runs: usize) -> Option<f64> {
+    let mut params = same_test_params(ttc);
+    params.push(Box::new(runs));
+
+    conn.query_row(
+        &format!(
+            "SELECT avg(wpm) FROM (SELECT wpm FROM run WHERE {} ORDER BY run_id DESC LIMIT ?)",
+            SAME_TEST
+        ),
+        params_from_iter(params),
+        |row| row.get(0),
+    )
+    .ok()
+}
+
+/// a run as the progress chart plots it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProgressRun {
+    // unix seconds
+    pub date: i64,
+    // local date, yyyy-mm-dd
+    pub day: String,
+    pub wpm: f64,
+    pub acc: f64,
+}
+
+/// every run of the same test as ttc, oldest first
+pub fn get_progress(conn: &Connection, ttc: &TypingTestConfig) -> Result<Vec<ProgressRun>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT date, date(date, 'unixepoch', 'localtime'), wpm, acc
+        FROM run WHERE {} ORDER BY date, run_id",
+        SAME_TEST
+    ))?;
+    let rows = stmt.query_map(params_from_iter(same_test_params(ttc)), |row| {
+        Ok(ProgressRun {
+            date: row.get(0)?,
+            day: row.get(1)?,
+            wpm: row.get(2)?,
+            acc: row.get(3)?,
+        })
+    })?;
+    Ok(rows.collect::<Result<_, _>>()?)
+}
+
 pub fn get_test_id(conn: &Connection, test_name: &str) -> Result<usize, rusqlite::Error> {
     conn.query_row(
         "select test_id from test where test_name = ?",
@@ -117,8 +194,9 @@ pub fn encode_test_mod_bitflag(test_mods: &HashSet<TestMod>) -> u8 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::langs::quotes::Quote;
     use crate::settings::TestMod;
-    use crate::settings::TypingTestConfig;
+    use crate::settings::{BackspacePolicy, ErrorPolicy, TestVariant, TypingTestConfig};
     use rusqlite::Connection;
     use std::collections::HashSet;
 
@@ -137,6 +215,40 @@ mod tests {
         assert_eq!(five, 5_u8);
     }
 
+    #[test]
+    fn test_history_of_old_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE test (
+            test_id INTEGER PRIMARY KEY,
+            test_name TEXT UNIQUE
+            );
+            CREATE TABLE run (
+            run_id INTEGER PRIMARY KEY,
+            date INTEGER NOT NULL,
+            test_id INTEGER NOT NULL,
+            length INTEGER NOT NULL,
+            word_pool INTEGER NOT NULL,
+            correct_chars INTEGER NOT NULL,
+            mistakes INTEGER NOT NULL,
+            wpm REAL NOT NULL,
+            acc REAL NOT NULL,
+            mods INTEGER NOT NULL,
+            FOREIGN KEY (test_id) REFERENCES test (test_id) ON DELETE CASCADE
+            );
+            INSERT INTO test (test_name) VALUES ('english');
+            INSERT INTO run (date, test_id, length, mods, word_pool, correct_chars, mistakes, wpm, acc)
+            VALUES (0, 1, 25, 0, 5000, 120, 0, 80., 97.5);",
+        )
+        .unwrap();
+
+        let db = RunHistoryDatbase::with_connection(conn);
+        let runs = history::get_runs(&db.conn, 10).unwrap();
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].label, "english 25/5000 ");
+        assert!(!runs[0].replayable);
+    }
+
     #[test]
     fn test_get_max_wpm() {
         let mut conn = RunHistoryDatbase {
@@ -160,4 +272,127 @@ mod tests {
         assert!(max_wpm - f64::EPSILON <= should_be_max_wpm);
         assert!(max_wpm + f64::EPSILON >= should_be_max_wpm);
     }
+
+    #[test]
+    fn test_get_average_wpm() {
+        let mut conn = RunHistoryDatbase {
+            conn: Connection::open_in_memory().unwrap(),
+        };
+        init::init_db(&mut conn.conn).unwrap();
+        let mut ttc = TypingTestConfig::default();
+        assert!(get_average_wpm(&conn.conn, &ttc, 2).is_none());
+
+        for wpm in [200., 60., 80.] {
+            ttc.test_summary.wpm = wpm;
+            conn.save(&ttc);
+        }
+
+        let average = get_average_wpm(&conn.conn, &ttc, 2).unwrap();
+        assert!((average - 70.).abs() < f64::EPSILON);
+    }
+
+    #[test]
+    fn test_get_progress() {
+        let mut conn = RunHistoryDatbase {
+            conn: Connection::open_in_memory().unwrap(),
+        };
+        init::init_db(&mut conn.conn).unwrap();
+        let mut ttc = TypingTestConfig::default();
+        assert!(get_progress(&conn.conn, &ttc).unwrap().is_empty());
+
+        for (wpm, acc) in [(60., 90.), (75., 95.)] {
+            ttc.test_summary.wpm = wpm;
+            ttc.test_summary.acc = acc;
+            conn.save(&ttc);
+        }
+        // other tests and failed runs stay out of it
+        ttc.test_summary.failed = true;
+        conn.save(&ttc);
+        ttc.test_summary.failed = false;
+        ttc.length += 1;
+        conn.save(&ttc);
+        ttc.length -= 1;
+
+        let progress = get_progress(&conn.conn, &ttc).unwrap();
+        let wpms: Vec<(f64, f64)> = progress.iter().map(|run| (run.wpm, run.acc)).collect();
+        assert_eq!(wpms, vec![(60., 90.), (75., 95.)]);
+        assert_eq!(progress[0].day.len(), "yyyy-mm-dd".len());
+    }
+
+    #[test]
+    fn test_get_max_wpm_per_duration() {
+        let mut conn = RunHistoryDatbase {
+            conn: Connection::open_in_memory().unwrap(),
+        };
+        init::init_db(&mut conn.conn).unwrap();
+        let mut ttc = TypingTestConfig {
+            variant: TestVariant::Timed,
+            duration: 15,
+            ..Default::default()
+        };
+
+        ttc.test_summary.wpm = 120.;
+        conn.save(&ttc);
+
+        ttc.duration = 60;
+        assert!(get_max_wpm(&conn.conn, &ttc).is_none());
+        ttc.test_summary.wpm = 90.;
+        conn.save(&ttc);
+
+        let max_wpm = get_max_wpm(&conn.conn, &ttc).unwrap();
+        assert!((max_wpm - 90.).abs() < f64::EPSILON);
+
+        // standard runs of the same word list don't mix with the timed ones
+        ttc.variant = TestVariant::Standard;
+        assert!(get_max_wpm(&conn.conn, &ttc).is_none());
+
+        // nor do runs with stricter rules
+        ttc.variant = TestVariant::Timed;
+        ttc.error_policy = ErrorPolicy::StopOnLetter;
+        assert!(get_max_wpm(&conn.conn, &ttc).is_none());
+
+        ttc.error_policy = ErrorPolicy::Free;
+        ttc.backspace = BackspacePolicy::Off;
+        assert!(get_max_wpm(&conn.conn, &ttc).is_none());
+
+        // failed runs never make it to the top
+        ttc.test_summary.wpm = 200.;
+        ttc.test_summary.failed = true;
+        conn.save(&ttc);
+        assert!(get_max_wpm(&conn.conn, &ttc).is_none());
+    }
+
+    #[test]
+    fn test_get_max_wpm_per_quote() {
+        let mut conn = RunHistoryDatbase {
+            conn: Connection::open_in_memory().unwrap(),
+        };
+        init::init_db(&mut conn.conn).unwrap();
+
+        let quote = |id: usize| Quote {
+            id,
+            text: "Brevity is the soul of wit.".to_string(),
+            source: "Hamlet".to_string(),
+            author: "William Shakespeare".to_string(),
+        };
+
+        let mut ttc = TypingTestConfig {
+            variant: TestVariant::Quote,
+            quote: Some(quote(1)),
+            ..Default::default()
+        };
+        ttc.test_summary.wpm = 100.;
+        conn.save(&ttc);
+
+        ttc.quote = Some(quote(2));
+        ttc.test_summary.wpm = 70.;
+        conn.save(&ttc);
+
+        let max_wpm = get_max_wpm(&conn.conn, &ttc).unwrap();
+        assert!((max_wpm - 70.).abs() < f64::EPSILON);
+
+        // the word list of the same name keeps its own records
+        ttc.variant = TestVariant::Standard;
+        assert!(get_max_wpm(&conn.conn, &ttc).is_none());
+    }
 }
@@ -0,0 +1,64 @@
+use crate::application::App;
+use crate::browser::{SortKey, PAGE};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+pub fn handle(key: KeyEvent, app: &mut App) {
+    if let KeyModifiers::CONTROL = key.modifiers {
+        if let KeyCode::Char('c') = key.code {
+            app.stop();
+        }
+        return;
+    }
+
+    let browser = &mut app.browser;
+
+    // / starts typing the test name filter, enter or esc finishes it
+    if browser.typing_filter {
+        match key.code {
+            KeyCode::Char(c) => browser.push_filter(c),
+            KeyCode::Backspace => browser.pop_filter(),
+            KeyCode::Enter | KeyCode::Esc => browser.typing_filter = false,
+            _ => (),
+        }
+        return;
+    }
+
+    match key.code {
+        KeyCode::Esc if browser.details => browser.details = false,
+        KeyCode::Esc => app.change_to_settings(),
+        KeyCode::Enter => browser.details ^= true,
+
+        KeyCode::Down => browser.scroll(1),
+        KeyCode::Up => browser.scroll(-1),
+        KeyCode::PageDown => browser.scroll(PAGE as isize),
+        KeyCode::PageUp => browser.scroll(-(PAGE as isize)),
+        KeyCode::Home => browser.first(),
+        KeyCode::End => browser.last(),
+
+        KeyCode::Char(c) => match c {
+            'j' => browser.scroll(1),
+            'k' => browser.scroll(-1),
+            'g' => browser.first(),
+            'G' => browser.last(),
+
+            'd' => browser.sort_by(SortKey::Date),
+            'w' => browser.sort_by(SortKey::Wpm),
+            'a' => browser.sort_by(SortKey::Acc),
+
+            '/' => browser.typing_filter = true,
+            'm' => browser.next_mod_filter(),
+            'c' => browser.clear_filters(),
+
+            'p' => {
+                let replayable = browser.selected().filter(|run| run.replayable);
+                if let Some(run_id) = replayable.map(|run| run.id) {
+                    app.replay_saved_run(run_id, |app| app.change_to_history());
+                }
+            }
+            'q' => app.stop(),
+            _ => (),
+        },
+
+        _ => (),
+    }
+}
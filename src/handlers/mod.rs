@@ -1,8 +1,24 @@
+pub mod history;
 pub mod post;
+pub mod progress;
+pub mod replay;
 pub mod settings;
 pub mod typer;
 
 use crate::application::App;
 use crossterm::event::KeyEvent;
+use std::time::Duration;
 
 pub type KeyHandler = fn(KeyEvent, &mut App);
+
+/// Signature of a function called on every turn of the main loop
+/// returns how long the loop may wait for a key event before ticking again
+pub type Ticker = fn(&mut App) -> Duration;
+
+/// default wait between redraws
+pub const TICK: Duration = Duration::from_millis(250);
+
+/// ticker for screens that don't care about the passing time
+pub fn idle(_app: &mut App) -> Duration {
+    TICK
+}
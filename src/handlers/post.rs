@@ -6,7 +6,12 @@ pub fn handle(key: KeyEvent, app: &mut App) {
         KeyCode::Esc => app.stop(),
 
         KeyCode::Tab => {
-            app.reset_test();
+            app.next_test();
+            app.change_to_test();
+        }
+
+        KeyCode::BackTab => {
+            app.retry_test();
             app.change_to_test();
         }
 
@@ -20,6 +25,17 @@ pub fn handle(key: KeyEvent, app: &mut App) {
             match c {
                 'q' => app.stop(),
                 's' => app.change_to_settings(),
+                'p' => app.replay_last_run(),
+                'k' => app.settings.postbox.cumulative ^= true,
+                'w' => app.practice_weak_words(),
+                '1' => app.settings.postbox.series.average ^= true,
+                '2' => app.settings.postbox.series.rolling ^= true,
+                '3' => app.settings.postbox.series.burst ^= true,
+                '4' => app.settings.postbox.series.mistakes ^= true,
+                'r' => {
+                    app.retry_test();
+                    app.change_to_test();
+                }
                 _ => (),
             }
         }
@@ -0,0 +1,19 @@
+use crate::application::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+pub fn handle(key: KeyEvent, app: &mut App) {
+    if let KeyModifiers::CONTROL = key.modifiers {
+        if let KeyCode::Char('c') = key.code {
+            app.stop();
+        }
+        return;
+    }
+
+    match key.code {
+        KeyCode::Esc => (app.progress.back)(app),
+        KeyCode::Char('+') | KeyCode::Char('=') => app.progress.widen(),
+        KeyCode::Char('-') => app.progress.narrow(),
+        KeyCode::Char('q') => app.stop(),
+        _ => (),
+    }
+}
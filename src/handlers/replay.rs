@@ -0,0 +1,37 @@
+use super::TICK;
+use crate::application::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::Duration;
+
+/// how far h and l move the replay
+const SCRUB: Duration = Duration::from_secs(1);
+
+pub fn handle(key: KeyEvent, app: &mut App) {
+    if let KeyModifiers::CONTROL = key.modifiers {
+        if let KeyCode::Char('c') = key.code {
+            app.stop();
+        }
+        return;
+    }
+
+    let replay = &mut app.replay;
+    match key.code {
+        KeyCode::Esc => (replay.back)(app),
+        KeyCode::Char(' ') => replay.toggle_pause(),
+        KeyCode::Left | KeyCode::Char('h') => replay.scrub_back(SCRUB),
+        KeyCode::Right | KeyCode::Char('l') => replay.scrub_forward(SCRUB),
+        KeyCode::Char(',') => replay.step_back(),
+        KeyCode::Char('.') => replay.step_forward(),
+        KeyCode::Home | KeyCode::Char('0') => replay.seek(Duration::ZERO),
+        KeyCode::Char(c @ ('1' | '2' | '4')) => {
+            replay.speed = c.to_digit(10).expect("matched a digit");
+        }
+        KeyCode::Char('q') => app.stop(),
+        _ => (),
+    }
+}
+
+/// wakes up right when the next keystroke is due
+pub fn tick(app: &mut App) -> Duration {
+    app.replay.advance().map_or(TICK, |due| due.min(TICK))
+}
@@ -3,11 +3,7 @@ use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 
 pub fn handle(key: KeyEvent, app: &mut App) {
     match key.code {
-        KeyCode::Esc => {
-            if app.settings.escape() {
-                app.stop();
-            }
-        }
+        KeyCode::Esc if app.settings.escape() => app.stop(),
 
         KeyCode::Tab => {
             app.reset_test();
@@ -33,6 +29,8 @@ pub fn handle(key: KeyEvent, app: &mut App) {
                     app.settings.escape();
                 }
 
+                'r' => app.open_history(),
+                'g' => app.open_progress(|app| app.change_to_settings()),
                 'q' => app.stop(),
                 _ => {}
             }
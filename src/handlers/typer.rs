@@ -1,14 +1,32 @@
+use super::TICK;
 use crate::application::App;
+use crate::settings::{IdlePolicy, TestVariant};
+use crate::typer::{NEWLINE, TAB};
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::time::Duration;
 
 /// handles keys during test
 pub fn handle(key: KeyEvent, app: &mut App) {
     let test = &mut app.test;
+
+    // Ctrl + p stops and starts the clock, any other key starts it as well
+    if key.modifiers == KeyModifiers::CONTROL && key.code == KeyCode::Char('p') {
+        if test.is_paused() {
+            test.wake();
+        } else {
+            test.pause();
+        }
+        return;
+    }
+    test.wake();
+    // samples due before this key are taken without it
+    test.sample_wpm();
+
     // well doing this in terminal was a bad idea XD
     // Ctrl + Backspace registers as weird thing in terminals
     // I got ctrl(h) and ctrl(7) among others
     // but the ctrl is always there
-    // The following code thus interprets everything with ctrl mod except ctrl+c
+    // The following code thus interprets everything with ctrl mod except ctrl+c (and ctrl+p)
     // as ctrl + backspace
     // not pretty but it is what is for now
     if let KeyModifiers::CONTROL = key.modifiers {
@@ -19,46 +37,76 @@ pub fn handle(key: KeyEvent, app: &mut App) {
             }
         }
 
-        test.undo_word();
-        test.set_next_char();
+        if test.can_undo() {
+            test.undo_word();
+            test.set_next_char();
+        }
 
         return;
     }
 
-    match key.code {
-        KeyCode::Char(c) => {
-            if test.on_char(c) {
-                // TEST ENDS
-                // we summarize and write to db?
-                let summary = test.summarize();
-                app.settings.save_test_results(summary);
-
-                // app.settings.test_cfg.test_summary = summary;
-
-                // // If record is beat the historic_max_wpm but the
-                // // previous one is cached so it can be displayed in
-                // // the post screen
-                // let history_max_wpm: f64 =
-                //     app.settings.get_current_historic_max_wpm().unwrap_or(0.);
-                // app.postbox.cached_historic_wpm = history_max_wpm;
-                // let final_wpm = app.settings.test_cfg.test_summary.wpm;
-
-                // if final_wpm > history_max_wpm {
-                //     app.settings.update_historic_max_wpm(final_wpm);
-                // }
-
-                // app.settings.save_run_to_database();
-                app.change_to_post();
-            }
+    // code tests let you type line breaks and tabs
+    let typed = match key.code {
+        KeyCode::Char(c) => Some(c),
+        KeyCode::Enter if app.settings.test_cfg.variant == TestVariant::Code => Some(NEWLINE),
+        KeyCode::Tab if test.current_char == TAB => Some(TAB),
+        _ => None,
+    };
+
+    if let Some(c) = typed {
+        if test.on_char(c) {
+            app.finish_test();
+        } else {
+            app.feed_test();
         }
+        return;
+    }
 
-        KeyCode::Backspace => test.undo_char(),
+    match key.code {
+        KeyCode::Backspace if test.can_undo() => test.undo_char(),
         KeyCode::Tab => app.reset_test(),
+        KeyCode::BackTab => app.retry_test(),
         KeyCode::Esc => app.change_to_settings(),
         _ => (),
     }
 }
 
+/// redraws often enough for the extra carets to glide
+const CARET_TICK: Duration = Duration::from_millis(50);
+
+/// takes the wpm samples, ends timed tests right at the deadline
+/// even if no key is pressed and deals with the typist walking away
+pub fn tick(app: &mut App) -> Duration {
+    app.test.sample_wpm();
+    if app.test.is_idle() {
+        match app.test.idle_policy {
+            IdlePolicy::Abort => app.reset_test(),
+            _ => app.test.pause_idle(),
+        }
+    }
+
+    let mut tick = if app.test.pace_wpm.is_some() || !app.test.ghost.is_empty() {
+        CARET_TICK
+    } else {
+        TICK
+    };
+    for wait in [app.test.idle_in(), app.test.sample_in()]
+        .into_iter()
+        .flatten()
+    {
+        tick = tick.min(wait);
+    }
+
+    match app.test.time_left() {
+        Some(Duration::ZERO) => {
+            app.finish_test();
+            TICK
+        }
+        Some(left) => left.min(tick),
+        None => tick,
+    }
+}
+
 // TODO i can write some db test here too I guess
 #[cfg(test)]
 mod tests {
@@ -67,8 +115,7 @@ mod tests {
     use crate::settings::Settings;
     use crossterm::event::{KeyCode, KeyEvent};
     use rusqlite::Connection;
-    use std::thread;
-    use std::time::Duration;
+    use std::time::{Duration, Instant};
 
     fn get_test_app<'a>() -> App<'a> {
         let mut settings = Settings {
@@ -144,6 +191,107 @@ mod tests {
         go_thorugh_test_n_times(1)
     }
 
+    #[test]
+    fn timed_test_never_runs_out_of_words() {
+        use crate::settings::TestVariant;
+
+        let mut app = get_test_app();
+        app.settings.test_cfg.variant = TestVariant::Timed;
+        app.reset_test();
+
+        let klucznik_ptr = app.key_handler as usize;
+        for _ in 0..3 {
+            let key_events = generate_key_events_passing_standart_test(&app);
+            for kv in key_events {
+                app.handle_key_event(kv);
+            }
+        }
+
+        assert_eq!(klucznik_ptr, app.key_handler as usize);
+        assert!(!app.test.down.is_empty());
+    }
+
+    #[test]
+    fn go_through_quote_test() {
+        use crate::settings::TestVariant;
+
+        let mut app = get_test_app();
+        app.settings.test_cfg.variant = TestVariant::Quote;
+        app.reset_test();
+        assert!(app.settings.test_cfg.quote.is_some());
+
+        let klucznik_ptr = app.key_handler as usize;
+        for kv in generate_key_events_passing_standart_test(&app) {
+            app.handle_key_event(kv);
+        }
+        assert_ne!(klucznik_ptr, app.key_handler as usize);
+        assert!(app.settings.test_cfg.test_summary.wpm > 0.);
+    }
+
+    #[test]
+    fn go_through_custom_text_parts() {
+        let mut app = get_test_app();
+        let sentence = "the quick brown fox jumps over the lazy dog. ".repeat(6);
+        app.settings
+            .set_custom_text("fox", &sentence.repeat(2))
+            .unwrap();
+        app.reset_test();
+        assert_eq!(app.settings.test_cfg.gib_identity().text_id, 0);
+
+        for kv in generate_key_events_passing_standart_test(&app) {
+            app.handle_key_event(kv);
+        }
+        assert!(app.settings.test_cfg.test_summary.wpm > 0.);
+
+        // tab on the post screen moves on to the second part
+        app.handle_key_event(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(app.settings.test_cfg.gib_identity().text_id, 1);
+    }
+
+    #[test]
+    fn practice_gives_way_to_the_test() {
+        let mut app = get_test_app();
+        let test = app.settings.test_cfg.gib_identity();
+
+        app.settings.start_practice("slow words").unwrap();
+        let slow = app.settings.test_cfg.gib_identity();
+        app.settings.start_practice("missed words").unwrap();
+        app.reset_test();
+        // every practice text has its own records
+        assert_ne!(app.settings.test_cfg.gib_identity(), slow);
+
+        for kv in generate_key_events_passing_standart_test(&app) {
+            app.handle_key_event(kv);
+        }
+        assert!(app.settings.test_cfg.test_summary.wpm > 0.);
+
+        app.handle_key_event(KeyEvent::from(KeyCode::Tab));
+        assert_eq!(app.settings.test_cfg.gib_identity(), test);
+        assert!(!app.settings.end_practice());
+    }
+
+    #[test]
+    fn post_screen_of_a_run_shorter_than_a_second() {
+        use crate::painters::draw_post;
+        use crate::typer::Clock;
+        use tui::{backend::TestBackend, Terminal};
+
+        let mut app = get_test_app();
+        app.settings.set_custom_text("short", "hi").unwrap();
+        app.reset_test();
+        app.test.clock = Clock::Manual(Instant::now());
+
+        for kv in generate_key_events_passing_standart_test(&app) {
+            app.test.clock.advance(Duration::from_millis(100));
+            app.handle_key_event(kv);
+        }
+        assert!(app.settings.test_cfg.test_summary.wpm > 0.);
+        assert!(app.test.hoarder.wpms.is_empty());
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+        draw_post(&mut terminal, &mut app);
+    }
+
     // Testing results of typing test
     // TODO: Accuracy and such
 
@@ -154,13 +302,14 @@ mod tests {
         Duration::from_secs_f64(12. / f64::from(wpm))
     }
 
-    // this tests are hardware/os dependent
-    // which make them potentially bad
+    // the manual clock presses every key exactly on time
     fn wpm_test_setup(wpm: f64) {
         use crate::settings::TypingTestConfig;
+        use crate::typer::Clock;
 
         let delay = wpm_to_char_delay(wpm);
         let mut app = get_test_app();
+        app.test.clock = Clock::Manual(Instant::now());
 
         let cfg = TypingTestConfig {
             length: 500,
@@ -170,28 +319,28 @@ mod tests {
         app.test.reset(&cfg);
 
         let key_events = generate_key_events_passing_standart_test(&app);
+        let chars = key_events.len() as f64;
 
         for kv in key_events {
-            thread::sleep(delay);
+            app.test.clock.advance(delay);
             app.handle_key_event(kv);
         }
 
+        // the first key is given for free at 240 wpm
+        let elapsed = (chars - 1.) * delay.as_secs_f64() + 0.05;
         let final_wpm = app.test.summarize().wpm;
+        assert!((final_wpm - 12. * chars / elapsed).abs() < 1e-6);
 
-        // final_wmp can be lower by a thin margin given in the toleranca variable
-        // than the requested wpm, but it can never be higher
-        // as std::thread::sleep is guaranteed to sleep for
-        // at least the specified duration
-        let tolerated = 0.99 * wpm;
-        assert!(final_wpm < wpm);
-        assert!(final_wpm > tolerated);
+        // a sample for every second of the run
+        let hoarder = &app.test.hoarder;
+        assert_eq!(hoarder.wpms.len() as u64, elapsed as u64 / hoarder.seconds);
+        assert!(hoarder.wpms.iter().all(|sample| (sample - wpm).abs() < 5.));
     }
 
     #[test]
-    #[ignore]
     fn test_wpm_counting() {
-        // wpm_test_setup(60.);
-        // wpm_test_setup(140.);
+        wpm_test_setup(60.);
+        wpm_test_setup(140.);
         wpm_test_setup(220.);
     }
 
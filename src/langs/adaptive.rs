@@ -0,0 +1,53 @@
+//! word picking of the weak letters mod
+//! words are drawn from the word pool with replacement
+//! the more weak letters a word has the likelier it is to come up
+
+use super::read_word_pool;
+use crate::settings::TypingTestConfig;
+use rand::distributions::WeightedIndex;
+use rand::prelude::*;
+
+/// letters with the lowest confidence the test leans towards
+const WEAK_LETTERS: usize = 3;
+/// extra weight a word gets for every weak letter in it
+const WEAK_LETTER_BIAS: f64 = 4.;
+
+pub fn get_weighted_words(config: &TypingTestConfig, length: usize) -> Vec<String> {
+    let pool = read_word_pool(config);
+    pick_weighted(&pool, &config.confidence.weakest(WEAK_LETTERS), length)
+}
+
+fn word_weight(word: &str, weak: &[char]) -> f64 {
+    let hits = weak.iter().filter(|&&letter| word.contains(letter)).count();
+    1. + WEAK_LETTER_BIAS * hits as f64
+}
+
+fn pick_weighted(pool: &[String], weak: &[char], length: usize) -> Vec<String> {
+    let weights = pool.iter().map(|word| word_weight(word, weak));
+    let dist = WeightedIndex::new(weights).expect("word pool shouldn't be empty");
+    let mut rng = thread_rng();
+
+    (0..length)
+        .map(|_| pool[dist.sample(&mut rng)].clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pick_weighted() {
+        assert_eq!(word_weight("fizz", &['z', 'q']), 5.);
+        assert_eq!(word_weight("quiz", &['z', 'q']), 9.);
+
+        let pool = vec!["the".to_string(), "zoo".to_string()];
+        let picked = pick_weighted(&pool, &[], 10);
+        assert_eq!(picked.len(), 10);
+
+        // zoo is five times as likely
+        let picked = pick_weighted(&pool, &['z'], 1000);
+        let zoos = picked.iter().filter(|word| *word == "zoo").count();
+        assert!(zoos > 700, "{}", zoos);
+    }
+}
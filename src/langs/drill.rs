@@ -0,0 +1,102 @@
+//! drills of bigrams and trigrams
+//! tests are made of words containing the drilled n-grams in turns
+//! n-grams no word of the pool has are typed as repeated chunks
+
+use super::read_word_pool;
+use crate::settings::TypingTestConfig;
+use std::collections::HashMap;
+
+/// n-grams in a single drill
+pub const DRILLED_NGRAMS: usize = 5;
+/// times an n-gram is repeated when no word contains it
+const CHUNK_REPEATS: usize = 3;
+
+pub fn get_drill_words(config: &TypingTestConfig, length: usize) -> Vec<String> {
+    let pool = read_word_pool(config);
+    // nothing is known about the typist yet
+    let ngrams = match config.drill.is_empty() {
+        true => common_ngrams(&pool, DRILLED_NGRAMS),
+        false => config.drill.clone(),
+    };
+    pick_drill_words(&pool, &ngrams, length)
+}
+
+/// most frequent bigrams and trigrams of the pool, about half of each
+fn common_ngrams(pool: &[String], n: usize) -> Vec<String> {
+    let mut counts: [HashMap<String, usize>; 2] = Default::default();
+    for word in pool {
+        let chars: Vec<char> = word.chars().collect();
+        for (size, counts) in [2, 3].into_iter().zip(counts.iter_mut()) {
+            for window in chars.windows(size) {
+                *counts.entry(window.iter().collect()).or_default() += 1;
+            }
+        }
+    }
+
+    let [bigrams, trigrams] = counts.map(|counts| {
+        let mut ranked: Vec<(String, usize)> = counts.into_iter().collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        ranked.into_iter().map(|(ngram, _)| ngram)
+    });
+    bigrams
+        .take(n.div_ceil(2))
+        .chain(trigrams.take(n / 2))
+        .collect()
+}
+
+fn pick_drill_words(pool: &[String], ngrams: &[String], length: usize) -> Vec<String> {
+    if ngrams.is_empty() {
+        return (0..length)
+            .map(|_| pool[fastrand::usize(..pool.len())].clone())
+            .collect();
+    }
+
+    let containing: Vec<Vec<&String>> = ngrams
+        .iter()
+        .map(|ngram| {
+            pool.iter()
+                .filter(|word| word.contains(ngram.as_str()))
+                .collect()
+        })
+        .collect();
+
+    (0..length)
+        .map(|i| {
+            let words = &containing[i % ngrams.len()];
+            match words.is_empty() {
+                true => ngrams[i % ngrams.len()].repeat(CHUNK_REPEATS),
+                false => words[fastrand::usize(..words.len())].clone(),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_drill_words() {
+        let pool = strings(&["the", "then", "thing", "sing", "zoo"]);
+        let ngrams = strings(&["th", "ing", "qx"]);
+
+        let words = pick_drill_words(&pool, &ngrams, 9);
+        for (i, word) in words.iter().enumerate() {
+            match i % 3 {
+                0 => assert!(word.contains("th"), "{}", word),
+                1 => assert!(word.contains("ing"), "{}", word),
+                _ => assert_eq!(word, "qxqxqx"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_common_ngrams() {
+        let pool = strings(&["the", "then", "other", "thin"]);
+        assert_eq!(common_ngrams(&pool, 3), vec!["th", "he", "the"]);
+    }
+}
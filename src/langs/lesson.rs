@@ -0,0 +1,192 @@
+//! lessons that unlock the letters of a word list one by one
+//! the most frequent letters come first, tests are made only of the
+//! unlocked ones and the next letter unlocks once the typist
+//! keeps up the target speed and accuracy for a few runs
+
+use super::read_word_pool;
+use crate::settings::TypingTestConfig;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::path::Path;
+
+/// letters unlocked right away
+pub const LESSON_START_LETTERS: usize = 6;
+pub const LESSON_WPM: f64 = 35.;
+pub const LESSON_ACC: f64 = 95.;
+/// runs in a row that have to hit both targets
+pub const LESSON_STREAK: u32 = 3;
+/// below this many real words pseudo-words are mixed in
+const MIN_LESSON_WORDS: usize = 30;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Lesson {
+    /// word list the lesson is made of
+    pub name: String,
+    /// every letter of the word list, most frequent first
+    pub letters: Vec<char>,
+    pub unlocked: usize,
+    /// runs in a row that hit the targets
+    pub streak: u32,
+}
+
+impl Lesson {
+    /// fresh lesson of the word list
+    pub fn from_word_list(name: &str, path: impl AsRef<Path>) -> io::Result<Self> {
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        for line in BufReader::new(File::open(path)?).lines() {
+            for c in line?.chars().filter(|c| c.is_alphabetic()) {
+                for lower in c.to_lowercase() {
+                    *counts.entry(lower).or_default() += 1;
+                }
+            }
+        }
+
+        let mut letters: Vec<(char, usize)> = counts.into_iter().collect();
+        letters.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let letters: Vec<char> = letters.into_iter().map(|(letter, _)| letter).collect();
+
+        Ok(Self {
+            name: name.to_string(),
+            unlocked: LESSON_START_LETTERS.min(letters.len()),
+            letters,
+            streak: 0,
+        })
+    }
+
+    pub fn unlocked_letters(&self) -> &[char] {
+        &self.letters[..self.unlocked]
+    }
+
+    /// letter unlocked most recently
+    pub fn newest(&self) -> Option<char> {
+        self.unlocked_letters().last().copied()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.unlocked == self.letters.len()
+    }
+
+    /// counts the run towards the streak
+    /// returns the letter it unlocked if any
+    pub fn record_run(&mut self, wpm: f64, acc: f64) -> Option<char> {
+        if wpm < LESSON_WPM || acc < LESSON_ACC {
+            self.streak = 0;
+            return None;
+        }
+
+        self.streak += 1;
+        if self.streak < LESSON_STREAK || self.is_complete() {
+            return None;
+        }
+        self.streak = 0;
+        self.unlocked += 1;
+        self.newest()
+    }
+}
+
+pub fn get_lesson_words(config: &TypingTestConfig, length: usize) -> Vec<String> {
+    let lesson = config
+        .lesson
+        .as_ref()
+        .expect("lesson should be loaded before the test is prepared");
+    pick_lesson_words(&read_word_pool(config), lesson, length)
+}
+
+fn pick_lesson_words(pool: &[String], lesson: &Lesson, length: usize) -> Vec<String> {
+    let letters = lesson.unlocked_letters();
+    let mut words: Vec<String> = pool
+        .iter()
+        .filter(|word| word.chars().all(|c| letters.contains(&c)))
+        .cloned()
+        .collect();
+
+    while words.len() < MIN_LESSON_WORDS {
+        words.push(pseudo_word(letters));
+    }
+
+    // the newest letter needs the practice the most
+    let newest: Vec<&String> = match lesson.newest() {
+        Some(newest) => words.iter().filter(|word| word.contains(newest)).collect(),
+        None => vec![],
+    };
+
+    (0..length)
+        .map(|i| match i % 2 == 0 && !newest.is_empty() {
+            true => newest[fastrand::usize(..newest.len())].clone(),
+            false => words[fastrand::usize(..words.len())].clone(),
+        })
+        .collect()
+}
+
+/// 2 to 5 random unlocked letters
+fn pseudo_word(letters: &[char]) -> String {
+    (0..fastrand::usize(2..=5))
+        .map(|_| letters[fastrand::usize(..letters.len())])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lesson(letters: &str, unlocked: usize) -> Lesson {
+        Lesson {
+            name: "english".to_string(),
+            letters: letters.chars().collect(),
+            unlocked,
+            streak: 0,
+        }
+    }
+
+    #[test]
+    fn test_unlocking() {
+        let mut lesson = lesson("etaoin", 5);
+        assert_eq!(lesson.unlocked_letters(), ['e', 't', 'a', 'o', 'i']);
+
+        assert_eq!(lesson.record_run(60., 98.), None);
+        assert_eq!(lesson.record_run(60., 98.), None);
+        // too sloppy, the streak starts over
+        assert_eq!(lesson.record_run(60., 80.), None);
+        assert_eq!(lesson.streak, 0);
+
+        for _ in 1..LESSON_STREAK {
+            assert_eq!(lesson.record_run(LESSON_WPM, LESSON_ACC), None);
+        }
+        assert_eq!(lesson.record_run(LESSON_WPM, LESSON_ACC), Some('n'));
+        assert!(lesson.is_complete());
+
+        for _ in 0..LESSON_STREAK {
+            assert_eq!(lesson.record_run(100., 100.), None);
+        }
+        assert_eq!(lesson.unlocked, 6);
+    }
+
+    #[test]
+    fn test_lesson_words_use_unlocked_letters() {
+        let pool: Vec<String> = ["tea", "eat", "note", "ate", "zebra"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect();
+        let lesson = lesson("etaonz", 3);
+
+        let words = pick_lesson_words(&pool, &lesson, 50);
+        assert_eq!(words.len(), 50);
+        for word in &words {
+            assert!(word.chars().all(|c| "eta".contains(c)), "{}", word);
+        }
+        // every other word has the newest letter
+        assert!(words.iter().step_by(2).all(|word| word.contains('a')));
+    }
+
+    #[test]
+    fn test_letters_ordered_by_frequency() {
+        let path = std::env::temp_dir().join("smokey_lesson_test_words");
+        std::fs::write(&path, "aab\nbAc\na\n").unwrap();
+        let lesson = Lesson::from_word_list("test", &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(lesson.letters, vec!['a', 'b', 'c']);
+        assert_eq!(lesson.unlocked, 3);
+    }
+}
@@ -1,9 +1,14 @@
+mod adaptive;
+pub mod drill;
 mod helpers;
+pub mod lesson;
 mod punctuation;
+pub mod quotes;
+pub mod text;
 
 use crate::colorscheme::ToForeground;
 use crate::settings::{TestMod, TestVariant, TypingTestConfig};
-use crate::typer::TestColors;
+use crate::typer::{TestColors, NEWLINE, TAB};
 use helpers::{Capitalize, SpanIntake};
 use punctuation::{InnerWord, Punctuation, PunctuationInsertFrequency};
 use std::ffi::OsStr;
@@ -23,45 +28,95 @@ const SYMBOLS: [char; 14] = [
 
 const LIMIT: usize = 65;
 
+/// amount of words generated at once for timed tests
+/// the test asks for more when it's about to run out
+const TIMED_CHUNK_LENGTH: usize = 50;
+
 pub fn prepare_test<'a>(config: &TypingTestConfig, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
     match config.variant {
-        TestVariant::Standard => prepare_standart_test(config, colors),
+        TestVariant::Standard => prepare_standart_test(config, config.length, colors),
+        TestVariant::Timed => prepare_timed_test(config, colors),
         TestVariant::Script => prepare_script_test(config, colors),
+        TestVariant::Quote => prepare_quote_test(config, colors),
+        TestVariant::Code => prepare_code_test(config, colors),
+        TestVariant::Text => prepare_custom_test(config, colors),
+        TestVariant::Lesson => prepare_lesson_test(config, colors),
+        TestVariant::Drill => prepare_standart_test(config, config.length, colors),
     }
 }
 
+/// lessons leave out the mods as they would bring in locked characters
+fn prepare_lesson_test<'a>(config: &TypingTestConfig, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
+    let words = lesson::get_lesson_words(config, config.length);
+    words_to_lines(&words, colors)
+}
+
+fn prepare_custom_test<'a>(config: &TypingTestConfig, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
+    let text = config
+        .text
+        .as_ref()
+        .expect("custom text should be set before the test is prepared");
+    to_testable_span(text.current(), colors)
+}
+
+fn prepare_code_test<'a>(config: &TypingTestConfig, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
+    let script_output = call_script(config.get_code_file_path());
+    to_code_span(&script_output, colors)
+}
+
+fn prepare_quote_test<'a>(config: &TypingTestConfig, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
+    let quote = config
+        .quote
+        .as_ref()
+        .expect("quote should be picked before the test is prepared");
+    to_testable_span(&quote.text, colors)
+}
+
+/// chunks of timed tests keep the trailing space
+/// so the next chunk can be appended right after
+fn prepare_timed_test<'a>(config: &TypingTestConfig, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
+    let mut test = prepare_standart_test(config, TIMED_CHUNK_LENGTH, colors);
+    add_space_with_blank(&mut test[0], colors);
+    test
+}
+
 fn prepare_script_test<'a>(config: &TypingTestConfig, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
     let script_output = call_script(config.get_scripts_file_path());
     to_testable_span(&script_output, colors)
 }
 
-fn prepare_standart_test<'a>(config: &TypingTestConfig, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
-    let prep = get_shuffled_words(config);
+fn prepare_standart_test<'a>(
+    config: &TypingTestConfig,
+    length: usize,
+    colors: &TestColors,
+) -> Vec<Vec<Span<'a>>> {
+    let prep = get_shuffled_words(config, length);
+
+    match config.mods.is_empty() {
+        true => words_to_lines(&prep, colors),
+        false => prepare_modded_test(config, &prep, colors),
+    }
+}
 
+fn words_to_lines<'a>(words: &[String], colors: &TestColors) -> Vec<Vec<Span<'a>>> {
     let mut test: Vec<Vec<Span>> = vec![];
     let mut tmp: Vec<Vec<Span>> = vec![vec![]];
     let mut count = 0;
 
-    match config.mods.is_empty() {
-        true => {
-            for word in &prep {
-                count += word.len() + 1;
-                if count > LIMIT {
-                    test.append(&mut tmp);
-                    count = word.len();
-                    tmp.push(vec![]);
-                }
-
-                for c in word.chars() {
-                    tmp[0].push_styled_char(c, colors.todo);
-                }
+    for word in words {
+        count += word.len() + 1;
+        if count > LIMIT {
+            test.append(&mut tmp);
+            count = word.len();
+            tmp.push(vec![]);
+        }
 
-                add_space_with_blank(&mut tmp[0], colors);
-            }
+        for c in word.chars() {
+            tmp[0].push_styled_char(c, colors.todo);
         }
 
-        false => return prepare_modded_test(config, &prep, colors),
-    };
+        add_space_with_blank(&mut tmp[0], colors);
+    }
 
     let last = tmp.len() - 1;
     tmp[last].pop();
@@ -71,7 +126,14 @@ fn prepare_standart_test<'a>(config: &TypingTestConfig, colors: &TestColors) ->
     test.into_iter().rev().collect()
 }
 
-fn get_shuffled_words(config: &TypingTestConfig) -> Vec<String> {
+fn get_shuffled_words(config: &TypingTestConfig, length: usize) -> Vec<String> {
+    if config.variant == TestVariant::Drill {
+        return drill::get_drill_words(config, length);
+    }
+    if config.mods.contains(&TestMod::WeakLetters) {
+        return adaptive::get_weighted_words(config, length);
+    }
+
     // This is quick and bad
     // TODO impl more robust system
     let words_file = config.get_words_file_path();
@@ -81,7 +143,7 @@ fn get_shuffled_words(config: &TypingTestConfig) -> Vec<String> {
     let mut line_iter = reader.lines();
     let mut container: Vec<String> = Vec::new();
 
-    let mut prng = Randorst::gen(config.length, 0..config.word_pool);
+    let mut prng = Randorst::gen(length, 0..config.word_pool);
     let mut last = prng.next().unwrap();
     let out = line_iter.nth(last).unwrap().unwrap();
     container.push(out);
@@ -101,6 +163,16 @@ fn get_shuffled_words(config: &TypingTestConfig) -> Vec<String> {
     container
 }
 
+/// the first word_pool words of the word list
+fn read_word_pool(config: &TypingTestConfig) -> Vec<String> {
+    let file = File::open(config.get_words_file_path()).expect("couldn't open file");
+    BufReader::new(file)
+        .lines()
+        .take(config.word_pool)
+        .map(|line| line.expect("couldn't read the word list"))
+        .collect()
+}
+
 fn add_space_with_blank(container: &mut Vec<Span>, colors: &TestColors) {
     container.push(Span::styled("", colors.wrong.fg()));
     container.push(Span::styled(" ", colors.todo.fg()));
@@ -138,6 +210,12 @@ fn to_testable_span<'a>(text: &str, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
         }
     }
 
+    // text doesn't have to end with whitespace
+    if !word.is_empty() {
+        add_space_with_blank(&mut word, colors);
+        tmp[0].append(&mut word);
+    }
+
     lines.append(&mut tmp);
     lines = lines.into_iter().rev().collect();
     lines[0].pop();
@@ -145,6 +223,103 @@ fn to_testable_span<'a>(text: &str, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
     lines
 }
 
+/// spans of a line are separated by this when the test is saved
+const SPAN_SEPARATOR: char = '\u{1f}';
+
+/// flattens the lines of a test so it can be stored and replayed
+/// blanks survive as empty spans, lines keep their order
+pub(crate) fn lines_to_text(lines: &[Vec<Span>]) -> String {
+    lines
+        .iter()
+        .map(|line| {
+            line.iter()
+                .map(|span| span.content.as_ref())
+                .collect::<Vec<_>>()
+                .join(&SPAN_SEPARATOR.to_string())
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// inverse of lines_to_text
+pub(crate) fn text_to_lines<'a>(text: &str, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
+    text.split('\n')
+        .map(|line| {
+            if line.is_empty() {
+                return vec![];
+            }
+            line.split(SPAN_SEPARATOR)
+                .map(|content| {
+                    let color = if content.is_empty() {
+                        colors.wrong
+                    } else {
+                        colors.todo
+                    };
+                    Span::styled(content.to_string(), color.fg())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// unlike to_testable_span keeps the structure of the text intact
+/// every source line becomes a line of the test that ends with NEWLINE
+/// lines longer than LIMIT are wrapped and blank lines are dropped
+/// indentation is kept as plain spaces and tabs
+/// the test decides whether to skip it or not
+pub(crate) fn to_code_span<'a>(text: &str, colors: &TestColors) -> Vec<Vec<Span<'a>>> {
+    let mut lines: Vec<Vec<Span>> = vec![];
+
+    for source_line in text.lines() {
+        let source_line = source_line.trim_end();
+        if source_line.is_empty() {
+            continue;
+        }
+
+        let mut line: Vec<Span> = vec![];
+        let mut word: Vec<Span> = vec![];
+        let mut chars = source_line.chars().peekable();
+
+        while let Some(&c) = chars.peek() {
+            match c {
+                ' ' => line.push_styled_char(' ', colors.todo),
+                '\t' => line.push_styled_char(TAB, colors.todo),
+                _ => break,
+            }
+            chars.next();
+        }
+
+        let indent = line.len();
+        let mut count = indent;
+
+        for c in chars {
+            count += 1;
+            if c == ' ' {
+                line.append(&mut word);
+                add_space_with_blank(&mut line, colors);
+            } else {
+                let c = if c == '\t' { TAB } else { c };
+                word.push_styled_char(c, colors.todo);
+                if count > LIMIT && line.len() > indent {
+                    lines.push(std::mem::take(&mut line));
+                    count = word.len();
+                }
+            }
+        }
+
+        line.append(&mut word);
+        line.push_styled_char(NEWLINE, colors.todo);
+        lines.push(line);
+    }
+
+    // there is nowhere to go after the last line
+    if let Some(last) = lines.last_mut() {
+        last.pop();
+    }
+
+    lines.into_iter().rev().collect()
+}
+
 fn prepare_modded_test<'a>(
     config: &TypingTestConfig,
     words: &[String],
@@ -325,6 +500,64 @@ mod tests {
     use crate::settings::TypingTestConfig;
     use crate::typer::TestColors;
 
+    fn collect_text(lines: &[Vec<Span>]) -> String {
+        lines
+            .iter()
+            .rev()
+            .flatten()
+            .map(|span| span.content.as_ref())
+            .collect()
+    }
+
+    #[test]
+    fn test_lines_to_text_and_back() {
+        let colors = TestColors::default();
+        let code = "fn main() {\n    let a = 1;\n}";
+        for lines in [
+            to_code_span(code, &colors),
+            to_testable_span("the quick brown fox", &colors),
+        ] {
+            let text = lines_to_text(&lines);
+            assert_eq!(text_to_lines(&text, &colors), lines);
+        }
+    }
+
+    #[test]
+    fn test_code_span_keeps_structure() {
+        let colors = TestColors::default();
+        let code = "fn main() {\n    let a  = 1;\n\n\tprintln!(\"{}\", a);   \n}\n";
+        let lines = to_code_span(code, &colors);
+
+        let text: Vec<String> = lines
+            .iter()
+            .rev()
+            .map(|line| line.iter().map(|span| span.content.as_ref()).collect())
+            .collect();
+
+        assert_eq!(
+            text,
+            vec![
+                "fn main() {↵",
+                "    let a  = 1;↵",
+                "⇥println!(\"{}\", a);↵",
+                "}",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_testable_span_keeps_last_word() {
+        let colors = TestColors::default();
+        let with_newline = to_testable_span("Brevity is  the soul\nof wit.\n", &colors);
+        let without_newline = to_testable_span("Brevity is  the soul\nof wit.", &colors);
+
+        assert_eq!(collect_text(&with_newline), "Brevity is the soul of wit.");
+        assert_eq!(
+            collect_text(&without_newline),
+            "Brevity is the soul of wit."
+        );
+    }
+
     #[test]
     fn test_prep() {
         let cfg = TypingTestConfig {
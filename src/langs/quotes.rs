@@ -0,0 +1,103 @@
+//! Quote collections live in storage/quotes
+//! each file is a toml document with a list of quotes
+//! ```toml
+//! [[quote]]
+//! id = 1
+//! text = "Brevity is the soul of wit."
+//! source = "Hamlet"
+//! author = "William Shakespeare"
+//! ```
+//! ids have to be unique within a collection as records are tracked per id
+
+use crate::settings::QuoteLength;
+use serde_derive::Deserialize;
+use std::fs;
+use std::path::Path;
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct Quote {
+    pub id: usize,
+    pub text: String,
+    pub source: String,
+    pub author: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct QuoteCollection {
+    quote: Vec<Quote>,
+}
+
+fn parse_collection(toml_string: &str) -> anyhow::Result<Vec<Quote>> {
+    let collection: QuoteCollection = toml::from_str(toml_string)?;
+    Ok(collection.quote)
+}
+
+/// picks a random quote of the requested length
+/// falls back to any quote if none of them fits
+pub fn pick_quote(path: impl AsRef<Path>, length: QuoteLength) -> anyhow::Result<Quote> {
+    let quotes = parse_collection(&fs::read_to_string(path)?)?;
+    choose(quotes, length).ok_or_else(|| anyhow::anyhow!("the quote collection is empty"))
+}
+
+fn choose(mut quotes: Vec<Quote>, length: QuoteLength) -> Option<Quote> {
+    let fitting: Vec<usize> = (0..quotes.len())
+        .filter(|&i| length.fits(quotes[i].text.chars().count()))
+        .collect();
+
+    if fitting.is_empty() {
+        if quotes.is_empty() {
+            return None;
+        }
+        let i = fastrand::usize(..quotes.len());
+        return Some(quotes.swap_remove(i));
+    }
+
+    let i = fitting[fastrand::usize(..fitting.len())];
+    Some(quotes.swap_remove(i))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COLLECTION: &str = r#"
+        [[quote]]
+        id = 1
+        text = "Brevity is the soul of wit."
+        source = "Hamlet"
+        author = "William Shakespeare"
+
+        [[quote]]
+        id = 7
+        text = "It is a truth universally acknowledged, that a single man in possession of a good fortune, must be in want of a wife."
+        source = "Pride and Prejudice"
+        author = "Jane Austen"
+    "#;
+
+    #[test]
+    fn test_choose_by_length() {
+        let quotes = parse_collection(COLLECTION).unwrap();
+        assert_eq!(quotes.len(), 2);
+
+        let short = choose(quotes.clone(), QuoteLength::Short).unwrap();
+        assert_eq!(short.id, 1);
+
+        let medium = choose(quotes.clone(), QuoteLength::Medium).unwrap();
+        assert_eq!(medium.author, "Jane Austen");
+
+        // nothing is thicc enough so anything goes
+        assert!(choose(quotes, QuoteLength::Thicc).is_some());
+        assert!(choose(vec![], QuoteLength::All).is_none());
+    }
+
+    #[test]
+    fn test_bundled_collection() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/storage/quotes/english");
+        let quotes = parse_collection(&fs::read_to_string(path).unwrap()).unwrap();
+
+        let mut ids: Vec<usize> = quotes.iter().map(|q| q.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), quotes.len(), "quote ids should be unique");
+    }
+}
@@ -0,0 +1,84 @@
+//! Arbitrary text handed over through the command line
+//! long texts are split into parts typed one after another
+
+/// parts don't end before this many words unless the text does
+const PART_MIN_WORDS: usize = 50;
+/// parts prefer to end with a sentence but never get longer than this
+const PART_MAX_WORDS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomText {
+    pub parts: Vec<String>,
+    // index of the part typed right now
+    pub part: usize,
+}
+
+impl CustomText {
+    /// returns None if there is nothing to type
+    pub fn new(text: &str) -> Option<Self> {
+        let parts = split_text(text);
+        if parts.is_empty() {
+            return None;
+        }
+        Some(Self { parts, part: 0 })
+    }
+
+    pub fn current(&self) -> &str {
+        &self.parts[self.part]
+    }
+
+    /// moves on to the next part, the text starts over after the last one
+    pub fn advance(&mut self) {
+        self.part = (self.part + 1) % self.parts.len();
+    }
+}
+
+fn split_text(text: &str) -> Vec<String> {
+    let mut parts = vec![];
+    let mut part: Vec<&str> = vec![];
+
+    for word in text.split_whitespace() {
+        part.push(word);
+
+        let ends_sentence = word.ends_with(['.', '!', '?']);
+        if (part.len() >= PART_MIN_WORDS && ends_sentence) || part.len() >= PART_MAX_WORDS {
+            parts.push(part.join(" "));
+            part.clear();
+        }
+    }
+
+    if !part.is_empty() {
+        parts.push(part.join(" "));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_text() {
+        assert!(CustomText::new(" \n\t").is_none());
+
+        let short = CustomText::new("Brevity is\nthe soul of wit.").unwrap();
+        assert_eq!(short.parts, vec!["Brevity is the soul of wit."]);
+
+        // sentences are kept whole once the part is long enough
+        let sentence = "word ".repeat(PART_MIN_WORDS - 1) + "end. ";
+        let text = sentence.repeat(2) + "tail";
+        let mut long = CustomText::new(&text).unwrap();
+        assert_eq!(long.parts.len(), 3);
+        assert!(long.current().ends_with("end."));
+        assert_eq!(long.parts[2], "tail");
+
+        // no sentence ends in sight
+        let endless = CustomText::new(&"word ".repeat(PART_MAX_WORDS + 1)).unwrap();
+        assert_eq!(endless.parts.len(), 2);
+
+        long.advance();
+        long.advance();
+        long.advance();
+        assert_eq!(long.part, 0);
+    }
+}
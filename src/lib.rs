@@ -3,10 +3,14 @@ mod langs;
 mod painters;
 
 pub mod application;
+pub mod browser;
 pub mod colorscheme;
 pub mod config;
 pub mod database;
+pub mod progress;
+pub mod replay;
 pub mod settings;
+pub mod stats;
 pub mod storage;
 pub mod typer;
 pub mod utils;
@@ -18,7 +22,7 @@ use simplelog::*;
 
 use application::App;
 use crossterm::event::{poll, read, Event as CEvent};
-use std::{fs::File, io::Stdout, time::Duration};
+use std::{fs::File, io::Stdout, time::Instant};
 use tui::{backend::CrosstermBackend, Terminal};
 use utils::termprep;
 
@@ -29,7 +33,6 @@ pub fn run(mut app: App, terminal: Term) -> crossterm::Result<()> {
     #[cfg(debug_assertions)]
     init_logger();
 
-    database::init::debug_init_db();
     app.reset_test();
 
     termprep::init();
@@ -41,14 +44,18 @@ pub fn run(mut app: App, terminal: Term) -> crossterm::Result<()> {
 
 fn main_loop(mut app: App, mut terminal: Term) -> crossterm::Result<()> {
     while app.is_alive {
+        let timeout = app.tick();
+
         // drawing to the screen
         app.paint(&mut terminal);
 
         // handling events
-        if poll(Duration::from_millis(250))? {
+        if poll(timeout)? {
             let read = read()?;
+            // keys are timed as soon as they are read
+            let at = Instant::now();
             if let CEvent::Key(event) = read {
-                app.handle_key_event(event)
+                app.handle_key_event_at(event, at)
             }
         }
     }
@@ -6,10 +6,15 @@
 //! by ukmrs https://github.com/ukmrs/smokey
 //! A simple typing test terminal UI app
 
-use smokey::{application::App, database, storage};
+use smokey::config::{try_parse_mods, TestOverride};
+use smokey::database::{self, aggregate::StatsFilter};
+use smokey::{application::App, settings::TestMod, storage};
 
-use clap::Parser;
-use std::io::stdout;
+use clap::{Parser, Subcommand};
+use std::collections::HashSet;
+use std::io::{self, stdout};
+use std::path::{Path, PathBuf};
+use std::{fs, process};
 use tui::{backend::CrosstermBackend, Terminal};
 
 fn main() -> crossterm::Result<()> {
@@ -24,7 +29,42 @@ fn main() -> crossterm::Result<()> {
     let backend = CrosstermBackend::new(sout);
     let terminal = Terminal::new(backend)?;
 
-    let app = App::from_config();
+    let mut app = App::from_config();
+
+    if let Some(run_id) = opt.replay {
+        // leaving the replay quits the app
+        if !app.replay_saved_run(run_id, |app| app.stop()) {
+            eprintln!("run {} can't be replayed", run_id);
+            process::exit(1);
+        }
+    }
+
+    if let Some((name, text)) = read_custom_text(&opt) {
+        if let Err(err) = app.settings.set_custom_text(&name, &text) {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
+
+    if let Some(Command::Progress {
+        name,
+        len,
+        time,
+        pool,
+        mods,
+    }) = opt.command
+    {
+        let test = TestOverride {
+            name,
+            len,
+            time,
+            pool,
+            mods: mods.as_deref().map(cli_mods),
+        };
+        test.apply(&mut app.settings.test_cfg);
+        // leaving the chart quits the app
+        app.open_progress(|app| app.stop());
+    }
 
     smokey::run(app, terminal)?;
     Ok(())
@@ -44,6 +84,95 @@ struct Opt {
     /// Prints out summaries of n most recent runs
     #[structopt(short, long, name = "n")]
     recent: Option<Option<usize>>,
+
+    /// Replays the run with the given id, ids are shown by --recent
+    #[structopt(short = 'p', long, name = "run id")]
+    replay: Option<i64>,
+
+    /// Types the text of the given file
+    #[structopt(short, long, name = "path")]
+    text: Option<PathBuf>,
+
+    /// Types the text of the given file, - reads it from stdin
+    #[structopt(name = "file", conflicts_with = "path")]
+    input: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Charts wpm and accuracy of every run of a test over time,
+    /// the test from smokey.toml unless told otherwise
+    Progress {
+        /// Name of the test as written in smokey.toml
+        #[clap(short, long)]
+        name: Option<String>,
+
+        /// Number of words
+        #[clap(short, long)]
+        len: Option<usize>,
+
+        /// Seconds of a timed test
+        #[clap(short, long)]
+        time: Option<u64>,
+
+        /// Number of the most common words the test picks from
+        #[clap(short, long)]
+        pool: Option<usize>,
+
+        /// Comma separated mods, none for no mods
+        #[clap(short, long, use_value_delimiter = true)]
+        mods: Option<Vec<String>>,
+    },
+    /// Prints the number of runs, time typed, wpm and accuracy of every test
+    Stats {
+        /// Only tests whose name contains it
+        #[clap(short, long)]
+        name: Option<String>,
+
+        /// Only tests with exactly these comma separated mods, none for no mods
+        #[clap(short, long, use_value_delimiter = true)]
+        mods: Option<Vec<String>>,
+
+        /// Only runs of the last n days
+        #[clap(short, long)]
+        days: Option<u32>,
+    },
+}
+
+/// returns the test name and the text to type if any was given
+fn read_custom_text(opt: &Opt) -> Option<(String, String)> {
+    let path = opt.text.as_ref().or(opt.input.as_ref())?;
+
+    let read = if path == Path::new("-") {
+        io::read_to_string(io::stdin()).map(|text| ("stdin".to_string(), text))
+    } else {
+        let name = path
+            .file_stem()
+            .map_or("text".into(), |stem| stem.to_string_lossy().to_string());
+        fs::read_to_string(path).map(|text| (name, text))
+    };
+
+    match read {
+        Ok(custom_text) => Some(custom_text),
+        Err(err) => {
+            eprintln!("couldn't read {:?}: {}", path, err);
+            process::exit(1);
+        }
+    }
+}
+
+/// exits on mods that don't exist instead of leaving them out
+fn cli_mods(mods: &[String]) -> HashSet<TestMod> {
+    match try_parse_mods(mods) {
+        Ok(mods) => mods,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    }
 }
 
 fn execute_info_requests(opt: &Opt) -> bool {
@@ -64,5 +193,17 @@ fn execute_info_requests(opt: &Opt) -> bool {
         database::RunHistoryDatbase::default().print_history(history_lines);
     }
 
+    if let Some(Command::Stats { name, mods, days }) = &opt.command {
+        should_exit = true;
+        let filter = StatsFilter {
+            name: name.clone(),
+            mods: mods
+                .as_deref()
+                .map(|mods| database::encode_test_mod_bitflag(&cli_mods(mods))),
+            days: *days,
+        };
+        database::RunHistoryDatbase::default().print_stats(&filter);
+    }
+
     should_exit
 }
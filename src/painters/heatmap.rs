@@ -0,0 +1,110 @@
+use crate::settings::{PostBox, HEATMAP_RUNS};
+use crate::stats::{KeyStat, KeyStats};
+
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+/// rows of the keyboard, each one shifted a bit more to the right
+const KEYBOARD: [&str; 4] = ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+/// keyboard rows, the space bar and the borders
+pub const HEATMAP_HEIGHT: u16 = 7;
+
+/// from good to bad
+const SCALE: [Color; 4] = [Color::Green, Color::Yellow, Color::LightRed, Color::Red];
+
+fn error_color(stat: &KeyStat) -> Color {
+    let rate = stat.error_rate();
+    if stat.misses == 0 {
+        SCALE[0]
+    } else if rate < 0.05 {
+        SCALE[1]
+    } else if rate < 0.15 {
+        SCALE[2]
+    } else {
+        SCALE[3]
+    }
+}
+
+/// keys are compared with the mean latency of all of them
+fn latency_color(stat: &KeyStat, mean_latency: f64) -> Color {
+    let ratio = stat.mean_latency().unwrap_or(mean_latency) / mean_latency;
+    if ratio < 0.85 {
+        SCALE[0]
+    } else if ratio < 1.15 {
+        SCALE[1]
+    } else if ratio < 1.5 {
+        SCALE[2]
+    } else {
+        SCALE[3]
+    }
+}
+
+/// error rate and latency of every key drawn on two keyboards
+pub fn draw_heatmap<B: Backend>(frame: &mut Frame<B>, area: Rect, postbox: &PostBox) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .split(area);
+
+    let (stats, span) = if postbox.cumulative {
+        (
+            &postbox.recent_key_stats,
+            format!("last {} runs", HEATMAP_RUNS),
+        )
+    } else {
+        (&postbox.key_stats, "this run".to_string())
+    };
+
+    let mut errors_title = format!("errors, {} (k)", span);
+    if let Some((key, rate)) = stats.most_missed() {
+        errors_title.push_str(&format!(" | worst {} {:.0}%", key, rate * 100.));
+    }
+
+    let mut latency_title = format!("latency, {}", span);
+    if let Some((key, latency)) = stats.slowest() {
+        latency_title.push_str(&format!(" | slowest {} {:.0}ms", key, latency));
+    }
+
+    let mean_latency = stats.mean_latency().unwrap_or(0.);
+    frame.render_widget(keyboard(stats, &errors_title, error_color), chunks[0]);
+    frame.render_widget(
+        keyboard(stats, &latency_title, |stat| {
+            latency_color(stat, mean_latency)
+        }),
+        chunks[1],
+    );
+}
+
+fn keyboard<'a>(
+    stats: &KeyStats,
+    title: &'a str,
+    color: impl Fn(&KeyStat) -> Color,
+) -> Paragraph<'a> {
+    let key_span = |key: char, label: String| match stats.get(key) {
+        Some(stat) => Span::styled(label, Style::default().fg(Color::Black).bg(color(stat))),
+        None => Span::styled(label, Style::default().fg(Color::DarkGray)),
+    };
+
+    let mut rows: Vec<Spans> = KEYBOARD
+        .iter()
+        .enumerate()
+        .map(|(shift, row)| {
+            let mut spans = vec![Span::raw(" ".repeat(shift + 1))];
+            spans.extend(row.chars().map(|key| key_span(key, format!(" {} ", key))));
+            Spans::from(spans)
+        })
+        .collect();
+
+    rows.push(Spans::from(vec![
+        Span::raw(" ".repeat(11)),
+        key_span(' ', format!("{:^13}", "space")),
+    ]));
+
+    Paragraph::new(rows).block(Block::default().title(title).borders(Borders::ALL))
+}
@@ -0,0 +1,188 @@
+use crate::application::App;
+use crate::browser::{Browser, SortKey};
+use crate::database::history::RunEntry;
+
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    Terminal,
+};
+
+const HEADERCOLOR: Color = Color::Blue;
+const FAILCOLOR: Color = Color::DarkGray;
+const HINTS: &str =
+    "j/k scroll, d/w/a sort, / name, m mods, c clear, enter details, p replay, esc back";
+
+pub fn draw_history<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
+    terminal
+        .draw(|frame| {
+            let browser = &mut app.browser;
+            let hover = app.settings.colors.hover;
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(4), Constraint::Min(0)].as_ref())
+                .vertical_margin(app.margin)
+                .horizontal_margin(app.margin)
+                .split(frame.size());
+
+            frame.render_widget(filters(browser, hover), chunks[0]);
+
+            let body = if browser.details {
+                Layout::default()
+                    .direction(Direction::Horizontal)
+                    .constraints([Constraint::Percentage(65), Constraint::Percentage(35)].as_ref())
+                    .split(chunks[1])
+            } else {
+                vec![chunks[1]]
+            };
+
+            if browser.details {
+                frame.render_widget(details(browser.selected()), body[1]);
+            }
+
+            let arrow = if browser.descending { "▼" } else { "▲" };
+            let title =
+                |name: &str, key: Option<SortKey>| match key.filter(|&key| key == browser.sort) {
+                    Some(_) => format!("{} {}", name, arrow),
+                    None => name.to_string(),
+                };
+            let header = Row::new(vec![
+                Cell::from("id"),
+                Cell::from(title("date", Some(SortKey::Date))),
+                Cell::from("test"),
+                Cell::from(title("wpm", Some(SortKey::Wpm))),
+                Cell::from(title("acc", Some(SortKey::Acc))),
+                Cell::from("raw"),
+                Cell::from("con"),
+                Cell::from("mis"),
+            ])
+            .style(Style::default().fg(HEADERCOLOR));
+
+            let rows: Vec<Row> = browser
+                .shown
+                .iter()
+                .map(|&i| {
+                    let run = &browser.runs[i];
+                    let row = Row::new(vec![
+                        run.id.to_string(),
+                        run.date.clone(),
+                        run.label.clone(),
+                        format!("{:.1}", run.wpm),
+                        format!("{:.1}", run.acc),
+                        format!("{:.1}", run.raw),
+                        format!("{:.0}%", run.consistency),
+                        run.mis.to_string(),
+                    ]);
+                    match run.failed {
+                        true => row.style(Style::default().fg(FAILCOLOR)),
+                        false => row,
+                    }
+                })
+                .collect();
+
+            let table = Table::new(rows)
+                .header(header)
+                .block(
+                    Block::default()
+                        .title(format!("runs ({})", browser.shown.len()))
+                        .borders(Borders::ALL),
+                )
+                .widths(&[
+                    Constraint::Length(6),
+                    Constraint::Length(19),
+                    Constraint::Min(16),
+                    Constraint::Length(7),
+                    Constraint::Length(7),
+                    Constraint::Length(6),
+                    Constraint::Length(5),
+                    Constraint::Length(4),
+                ])
+                .highlight_style(Style::default().fg(hover).add_modifier(Modifier::BOLD))
+                .highlight_symbol("> ");
+
+            frame.render_stateful_widget(table, body[0], &mut browser.state);
+        })
+        .expect("drawing history went oof");
+}
+
+/// the filters in use and the keys of the screen
+fn filters(browser: &Browser, hover: Color) -> Paragraph<'static> {
+    let name = match (browser.name_filter.is_empty(), browser.typing_filter) {
+        (true, false) => "any test".to_string(),
+        (_, false) => format!("test: {}", browser.name_filter),
+        (_, true) => format!("test: {}_", browser.name_filter),
+    };
+    let name_style = match browser.typing_filter {
+        true => Style::default().fg(hover),
+        false => Style::default(),
+    };
+
+    let txt = vec![
+        Spans::from(vec![
+            Span::styled(name, name_style),
+            Span::raw(format!(" | {}", browser.mod_filter)),
+        ]),
+        Spans::from(Span::styled(HINTS, Style::default().fg(Color::DarkGray))),
+    ];
+    Paragraph::new(txt).block(Block::default().title("history").borders(Borders::ALL))
+}
+
+/// everything saved about the selected run
+fn details(run: Option<&RunEntry>) -> Paragraph<'static> {
+    let block = Block::default().title("run").borders(Borders::ALL);
+    let run = match run {
+        Some(run) => run,
+        None => return Paragraph::new("no run selected").block(block),
+    };
+
+    let stat = |name: &str, value: String| {
+        Spans::from(vec![
+            Span::styled(format!("{:<12}", name), Style::default().fg(HEADERCOLOR)),
+            Span::raw(value),
+        ])
+    };
+
+    let mut txt = vec![
+        Spans::from(Span::styled(
+            run.label.clone(),
+            Style::default().add_modifier(Modifier::BOLD),
+        )),
+        Spans::from(run.date.clone()),
+        Spans::from(""),
+        stat("wpm", format!("{:.2}", run.wpm)),
+        stat("raw wpm", format!("{:.2}", run.raw)),
+        stat("cpm", format!("{:.0}", run.cpm)),
+        stat("accuracy", format!("{:.2}%", run.acc)),
+        stat("consistency", format!("{:.0}%", run.consistency)),
+        stat("correct", run.correct.to_string()),
+        stat("mistakes", run.mis.to_string()),
+        stat("corrected", run.fix.to_string()),
+    ];
+    if run.paused > 0. {
+        txt.push(stat("afk", format!("{:.0}s", run.paused)));
+        txt.push(stat("wall wpm", format!("{:.2}", run.wall_wpm)));
+    }
+    txt.push(Spans::from(""));
+    txt.push(stat("errors", run.error_policy.to_string()));
+    txt.push(stat("backspace", run.backspace.to_string()));
+    if run.failed {
+        txt.push(Spans::from("failed"));
+    }
+    if run.retries > 0 {
+        txt.push(stat("retry", run.retries.to_string()));
+    }
+    txt.push(Spans::from(""));
+    txt.push(Spans::from(Span::styled(
+        match run.replayable {
+            true => "p replays the run",
+            false => "no keystrokes to replay",
+        },
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    Paragraph::new(txt).block(block)
+}
@@ -10,16 +10,23 @@
 //!
 //! All painters had been trained using Bob Ross' *The Joy of Painting*
 
+mod heatmap;
+mod history;
 mod post;
+mod progress;
+mod replay;
 mod settings;
 mod typer;
 
 use crate::{application::App, Term};
 
 // re-exports
-pub use post::draw_post;
+pub use history::draw_history;
+pub use post::{draw_failed, draw_post};
+pub use progress::draw_progress;
+pub use replay::draw_replay;
 pub use settings::draw_settings;
-pub use typer::draw_test_and_update;
+pub use typer::draw_test;
 
 /// Signature of a function responsible for drawing to the terminal
 pub type Painter = fn(&mut Term, &mut App);
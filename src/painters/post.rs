@@ -1,4 +1,10 @@
+use super::heatmap::{draw_heatmap, HEATMAP_HEIGHT};
 use crate::application::App;
+use crate::langs::lesson::LESSON_STREAK;
+use crate::settings::{FailPolicy, TestVariant};
+use crate::stats::{
+    burst_wpm, mistake_times, mistakes_per_second, rolling_wpm, WordReport, ROLLING_WINDOW,
+};
 
 use tui::{
     backend::Backend,
@@ -12,6 +18,12 @@ use tui::{
 
 const WINCOLOR: Color = Color::Yellow;
 const STANDARDCOLOR: Color = Color::Cyan;
+const FAILCOLOR: Color = Color::Red;
+const STATCOLOR: Color = Color::Gray;
+const ROLLINGCOLOR: Color = Color::Magenta;
+const BURSTCOLOR: Color = Color::Green;
+/// mistakes per second get a short chart under the wpm one
+const MISTAKES_HEIGHT: u16 = 5;
 
 pub fn draw_post<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
     terminal
@@ -29,13 +41,12 @@ pub fn draw_post<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
 
             let final_wpm = format!("{}", summary.wpm.round());
             let final_acc = format!("{}", summary.acc.round());
-            let diff = format!("{}", summary.wpm - app.settings.postbox.cached_historic_wpm)[..6]
-                .to_string();
+            let mut diff = format!("{}", summary.wpm - app.settings.postbox.cached_historic_wpm);
+            // round differences are shorter than that
+            diff.truncate(6);
 
             let secs: f64 = test.hoarder.seconds as f64;
             let length: f64 = test.hoarder.wpms.len() as f64;
-            let hoarder_max_wpm: f64 = test.hoarder.get_max_wpm();
-            let (hmin_wpm, hmax_wpm) = test.hoarder.get_min_max_wpm();
             let history_max_wpm: f64 = app.settings.postbox.cached_historic_wpm;
 
             let mut wpm_line_style = Style::default().fg(STANDARDCOLOR);
@@ -44,17 +55,36 @@ pub fn draw_post<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
                 wpm_line_style = Style::default().fg(WINCOLOR);
             }
 
-            let highest = f64::max(hoarder_max_wpm, history_max_wpm);
-
-            let up_txt = vec![
+            let mut up_txt = vec![
                 Spans::from(vec![
                     Span::raw("wpm: "),
                     Span::styled(final_wpm, Style::default().fg(wpm_line_style.fg.unwrap())),
                 ]),
+                Spans::from(vec![
+                    Span::raw("raw: "),
+                    Span::styled(
+                        format!("{}", summary.raw_wpm.round()),
+                        Style::default().fg(STATCOLOR),
+                    ),
+                ]),
+                Spans::from(vec![
+                    Span::raw("cpm: "),
+                    Span::styled(
+                        format!("{}", summary.cpm.round()),
+                        Style::default().fg(STATCOLOR),
+                    ),
+                ]),
                 Spans::from(vec![
                     Span::raw("acc: "),
                     Span::styled(final_acc, Style::default().fg(Color::Green)),
                 ]),
+                Spans::from(vec![
+                    Span::raw("con: "),
+                    Span::styled(
+                        format!("{}%", summary.consistency.round()),
+                        Style::default().fg(STATCOLOR),
+                    ),
+                ]),
                 Spans::from(vec![
                     Span::raw("mis: "),
                     Span::styled(
@@ -62,16 +92,62 @@ pub fn draw_post<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
                         Style::default().fg(test.colors.wrong),
                     ),
                 ]),
+                Spans::from(vec![
+                    Span::raw("fix: "),
+                    Span::styled(
+                        format!("{}", summary.corrected),
+                        Style::default().fg(STATCOLOR),
+                    ),
+                ]),
                 Spans::from(vec![Span::styled(diff, wpm_line_style)]),
             ];
+            // the wpm leaves the pauses out, wall counts them in
+            if summary.paused > 0. {
+                up_txt.push(Spans::from(vec![
+                    Span::raw("afk: "),
+                    Span::styled(
+                        format!("{}s", summary.paused.round()),
+                        Style::default().fg(STATCOLOR),
+                    ),
+                ]));
+                up_txt.push(Spans::from(vec![
+                    Span::raw("wall: "),
+                    Span::styled(
+                        format!("{}", summary.wall_wpm.round()),
+                        Style::default().fg(STATCOLOR),
+                    ),
+                ]));
+            }
+            let summary_height = up_txt.len() as u16 + 2;
 
             // TODO move this logic to TypingTestConfig???;
-            let graph_title = format!("{}", test_cfg);
+            let mut graph_title = format!("{}", test_cfg);
+            if summary.retries > 0 {
+                graph_title.push_str(&format!(" | retry {}", summary.retries));
+            }
+            match (app.settings.postbox.unlocked, &test_cfg.lesson) {
+                (Some(letter), _) => graph_title.push_str(&format!(" | unlocked {}", letter)),
+                (None, Some(lesson))
+                    if test_cfg.variant == TestVariant::Lesson && !lesson.is_complete() =>
+                {
+                    graph_title.push_str(&format!(" | streak {}/{}", lesson.streak, LESSON_STREAK))
+                }
+                _ => {}
+            }
 
             let block = Paragraph::new(up_txt)
                 .block(Block::default().title("summary").borders(Borders::ALL));
 
-            frame.render_widget(block, chunks[0]);
+            let left_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(summary_height), Constraint::Min(0)].as_ref())
+                .split(chunks[0]);
+
+            frame.render_widget(block, left_chunks[0]);
+            frame.render_widget(
+                word_report(&app.settings.postbox.words, test.colors.wrong),
+                left_chunks[1],
+            );
 
             let mut wpm_dataset: Vec<(f64, f64)> = Vec::with_capacity(length as usize);
             let mut pb_dataset: Vec<(f64, f64)> = Vec::with_capacity(length as usize);
@@ -82,29 +158,93 @@ pub fn draw_post<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
                 pb_dataset.push((sec, history_max_wpm));
             }
 
-            let wpm_datasets = vec![
-                Dataset::default()
-                    // .name("pb")
-                    .marker(symbols::Marker::Braille)
-                    .style(Style::default().fg(Color::Blue))
-                    .graph_type(GraphType::Line)
-                    .data(&pb_dataset),
-                Dataset::default()
-                    // .name("wpm")
-                    .marker(symbols::Marker::Braille)
-                    .style(wpm_line_style)
-                    .graph_type(GraphType::Line)
-                    .data(&wpm_dataset),
-            ];
+            let samples: Vec<f64> = wpm_dataset.iter().map(|&(sec, _)| sec).collect();
+            let rolling_dataset =
+                rolling_wpm(&app.settings.postbox.timeline, &samples, ROLLING_WINDOW);
+            let burst_dataset = burst_wpm(&test.words);
+            // mistakes are bucketed like the wpm and marked on its line
+            let mistakes = mistake_times(&test.keystrokes);
+            let rates = mistakes_per_second(&mistakes, test.hoarder.seconds, length as usize);
+            let mistake_dataset: Vec<(f64, f64)> = rates
+                .iter()
+                .zip(&wpm_dataset)
+                .filter(|(&(_, rate), _)| rate > 0.)
+                .map(|(_, &point)| point)
+                .collect();
 
-            let x_labels: Vec<Span> = vec![
-                wpm_dataset[0].0,
-                wpm_dataset[(wpm_dataset.len() - 1) / 2].0,
-                wpm_dataset[wpm_dataset.len() - 1].0,
-            ]
-            .into_iter()
-            .map(|i| Span::styled(format!("{}", i), Style::default().fg(Color::Blue)))
-            .collect();
+            // the legend doubles as the list of keys toggling the series
+            let series = app.settings.postbox.series;
+            let mut wpm_datasets = vec![Dataset::default()
+                .name("pb")
+                .marker(symbols::Marker::Braille)
+                .style(Style::default().fg(Color::Blue))
+                .graph_type(GraphType::Line)
+                .data(&pb_dataset)];
+            let mut shown: Vec<&[(f64, f64)]> = vec![];
+
+            if series.average {
+                wpm_datasets.push(
+                    Dataset::default()
+                        .name("1 wpm")
+                        .marker(symbols::Marker::Braille)
+                        .style(wpm_line_style)
+                        .graph_type(GraphType::Line)
+                        .data(&wpm_dataset),
+                );
+                shown.push(&wpm_dataset);
+            }
+            if series.rolling {
+                wpm_datasets.push(
+                    Dataset::default()
+                        .name(format!("2 last {}s", ROLLING_WINDOW.as_secs()))
+                        .marker(symbols::Marker::Braille)
+                        .style(Style::default().fg(ROLLINGCOLOR))
+                        .graph_type(GraphType::Line)
+                        .data(&rolling_dataset),
+                );
+                shown.push(&rolling_dataset);
+            }
+            if series.burst {
+                wpm_datasets.push(
+                    Dataset::default()
+                        .name("3 burst")
+                        .marker(symbols::Marker::Dot)
+                        .style(Style::default().fg(BURSTCOLOR))
+                        .graph_type(GraphType::Scatter)
+                        .data(&burst_dataset),
+                );
+                shown.push(&burst_dataset);
+            }
+            if series.mistakes {
+                wpm_datasets.push(
+                    Dataset::default()
+                        .name("4 mistakes")
+                        .marker(symbols::Marker::Block)
+                        .style(Style::default().fg(test.colors.wrong))
+                        .graph_type(GraphType::Scatter)
+                        .data(&mistake_dataset),
+                );
+                shown.push(&mistake_dataset);
+            }
+
+            // the chart stretches to fit whatever is shown
+            let (hmin_wpm, hmax_wpm) = shown
+                .iter()
+                .flat_map(|data| data.iter().map(|&(_, wpm)| wpm))
+                .fold(None, |bounds: Option<(f64, f64)>, wpm| match bounds {
+                    Some((min, max)) => Some((min.min(wpm), max.max(wpm))),
+                    None => Some((wpm, wpm)),
+                })
+                .unwrap_or_else(|| test.hoarder.get_min_max_wpm());
+            let highest = f64::max(hmax_wpm, history_max_wpm);
+
+            // runs shorter than a second don't have a single sample
+            let sample_at = |i: usize| wpm_dataset.get(i).map_or(0., |&(sec, _)| sec);
+            let last = wpm_dataset.len().saturating_sub(1);
+            let x_labels: Vec<Span> = vec![sample_at(0), sample_at(last / 2), sample_at(last)]
+                .into_iter()
+                .map(|i| Span::styled(format!("{}", i), Style::default().fg(Color::Blue)))
+                .collect();
 
             // Only apply larger margin if personal best line would be unsightly close
             // to the chart upper frame
@@ -123,7 +263,16 @@ pub fn draw_post<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
                 .map(|i| Span::styled(format!("{}", i * 10), Style::default().fg(Color::Blue)))
                 .collect();
 
+            // both charts have to start at the same column to line the seconds up
+            let label_width = y_labels
+                .iter()
+                .map(Span::width)
+                .max()
+                .unwrap_or_default()
+                .max(x_labels[0].width() / 2);
+
             let chart = Chart::new(wpm_datasets)
+                .hidden_legend_constraints((Constraint::Ratio(1, 3), Constraint::Ratio(1, 2)))
                 .block(
                     Block::default()
                         .title(Span::styled(
@@ -150,7 +299,184 @@ pub fn draw_post<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
                         .labels(y_labels),
                 );
 
-            frame.render_widget(chart, chunks[1]);
+            // quotes get their attribution under the chart
+            let attribution_height = if test_cfg.quote.is_some() { 3 } else { 0 };
+            let right_chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Min(0),
+                        Constraint::Length(MISTAKES_HEIGHT),
+                        Constraint::Length(HEATMAP_HEIGHT),
+                        Constraint::Length(attribution_height),
+                    ]
+                    .as_ref(),
+                )
+                .split(chunks[1]);
+
+            frame.render_widget(chart, right_chunks[0]);
+
+            let mistake_bars: Vec<(f64, f64)> = rates
+                .iter()
+                .flat_map(|&(sec, rate)| [(sec, 0.), (sec, rate), (sec, 0.)])
+                .collect();
+            frame.render_widget(
+                mistake_chart(
+                    &mistake_bars,
+                    secs,
+                    length * secs,
+                    label_width,
+                    test.colors.wrong,
+                ),
+                right_chunks[1],
+            );
+            draw_heatmap(frame, right_chunks[2], &app.settings.postbox);
+
+            if let Some(quote) = &test_cfg.quote {
+                let attribution = Paragraph::new(Spans::from(vec![
+                    Span::raw(format!("{}, ", quote.author)),
+                    Span::styled(
+                        quote.source.as_str(),
+                        Style::default().add_modifier(Modifier::ITALIC),
+                    ),
+                ]))
+                .block(
+                    Block::default()
+                        .title(format!("quote #{}", quote.id))
+                        .borders(Borders::ALL),
+                );
+                frame.render_widget(attribution, right_chunks[3]);
+            }
         })
         .expect("drawing post went oof");
 }
+
+/// mistakes per second as bars under the wpm chart
+/// the y labels are padded to the width of the wpm chart ones
+fn mistake_chart<'a>(
+    bars: &'a [(f64, f64)],
+    from: f64,
+    to: f64,
+    label_width: usize,
+    wrong: Color,
+) -> Chart<'a> {
+    let top = bars.iter().map(|&(_, rate)| rate).fold(1., f64::max).ceil();
+    let y_labels: Vec<Span> = [0., top]
+        .iter()
+        .map(|rate| {
+            Span::styled(
+                format!("{:>width$}", rate, width = label_width),
+                Style::default().fg(Color::Blue),
+            )
+        })
+        .collect();
+
+    let bars = Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .style(Style::default().fg(wrong))
+        .graph_type(GraphType::Line)
+        .data(bars);
+
+    Chart::new(vec![bars])
+        .block(Block::default().title("mistakes/s").borders(Borders::ALL))
+        .x_axis(Axis::default().bounds([from, to]))
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0., top])
+                .labels(y_labels),
+        )
+}
+
+/// slowest and most missed words, w starts a test made of them
+fn word_report(report: &WordReport, wrong: Color) -> Paragraph<'static> {
+    let header = |title| Spans::from(Span::styled(title, Style::default().fg(Color::Blue)));
+    let entry = |word: &str, value: String, color: Color| {
+        Spans::from(vec![
+            Span::raw(format!("{} ", word)),
+            Span::styled(value, Style::default().fg(color)),
+        ])
+    };
+
+    let mut txt = vec![header("slowest")];
+    for (word, wpm) in &report.slowest {
+        txt.push(entry(word, wpm.round().to_string(), Color::DarkGray));
+    }
+
+    txt.push(Spans::from(""));
+    txt.push(header("most missed"));
+    for (word, misses) in &report.most_missed {
+        txt.push(entry(word, misses.to_string(), wrong));
+    }
+
+    Paragraph::new(txt).block(Block::default().title("words (w)").borders(Borders::ALL))
+}
+
+/// shown instead of the results when the run fails
+pub fn draw_failed<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
+    terminal
+        .draw(|frame| {
+            let summary = &app.settings.test_cfg.test_summary;
+            let test_cfg = &app.settings.test_cfg;
+
+            let reason = match test_cfg.fail_policy {
+                FailPolicy::MinAccuracy => {
+                    format!("accuracy dropped below {}%", test_cfg.min_acc)
+                }
+                _ => "a mistake was left behind".to_string(),
+            };
+
+            let txt = vec![
+                Spans::from(Span::styled(
+                    "failed",
+                    Style::default().fg(FAILCOLOR).add_modifier(Modifier::BOLD),
+                )),
+                Spans::from(Span::raw(reason)),
+                Spans::from(Span::raw("")),
+                Spans::from(vec![
+                    Span::raw("wpm: "),
+                    Span::styled(
+                        format!("{}", summary.wpm.round()),
+                        Style::default().fg(Color::Gray),
+                    ),
+                ]),
+                Spans::from(vec![
+                    Span::raw("acc: "),
+                    Span::styled(
+                        format!("{}", summary.acc.round()),
+                        Style::default().fg(Color::Gray),
+                    ),
+                ]),
+                Spans::from(vec![
+                    Span::raw("mis: "),
+                    Span::styled(
+                        format!("{}", summary.mistakes),
+                        Style::default().fg(app.test.colors.wrong),
+                    ),
+                ]),
+                Spans::from(Span::raw("")),
+                Spans::from(Span::styled(
+                    "tab for next test, r to retry, p to replay, s for settings",
+                    Style::default().fg(Color::DarkGray),
+                )),
+            ];
+
+            let area = Layout::default()
+                .direction(Direction::Vertical)
+                .vertical_margin(app.margin)
+                .horizontal_margin(app.margin)
+                .constraints([Constraint::Min(0)].as_ref())
+                .split(frame.size())[0];
+
+            let block = Paragraph::new(txt).alignment(Alignment::Center).block(
+                Block::default()
+                    .title(Span::styled(
+                        format!("{}", test_cfg),
+                        Style::default().fg(FAILCOLOR),
+                    ))
+                    .borders(Borders::ALL),
+            );
+            frame.render_widget(block, area);
+        })
+        .expect("drawing failed post went oof");
+}
@@ -0,0 +1,240 @@
+use crate::application::App;
+use crate::progress::{moving_average, personal_bests, steps, Progress};
+
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Span, Spans},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType, Paragraph},
+    Terminal,
+};
+
+const RUNCOLOR: Color = Color::Cyan;
+const AVERAGECOLOR: Color = Color::Magenta;
+const PBCOLOR: Color = Color::Yellow;
+const ACCCOLOR: Color = Color::Green;
+/// accuracy gets a shorter chart under the wpm one
+const ACC_HEIGHT: u16 = 10;
+const HINTS: &str = "+/- average more or fewer runs, esc back";
+
+pub fn draw_progress<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
+    terminal
+        .draw(|frame| {
+            let progress = &app.progress;
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints(
+                    [
+                        Constraint::Length(4),
+                        Constraint::Min(0),
+                        Constraint::Length(ACC_HEIGHT),
+                    ]
+                    .as_ref(),
+                )
+                .vertical_margin(app.margin)
+                .horizontal_margin(app.margin)
+                .split(frame.size());
+
+            if progress.runs.is_empty() {
+                let nothing = Paragraph::new(vec![
+                    Spans::from("no finished runs of this test yet"),
+                    Spans::from(Span::styled(HINTS, Style::default().fg(Color::DarkGray))),
+                ])
+                .block(
+                    Block::default()
+                        .title(progress.title.as_str())
+                        .borders(Borders::ALL),
+                );
+                frame.render_widget(nothing, chunks[0]);
+                return;
+            }
+
+            let wpm = progress.wpm();
+            let wpm_average = moving_average(&wpm, progress.window);
+            let bests = personal_bests(&wpm);
+            let span = progress.span().max(1.);
+            let pb_steps = steps(&bests, span);
+            let acc = progress.acc();
+            let acc_average = moving_average(&acc, progress.window);
+
+            frame.render_widget(summary(progress, &wpm_average, &bests), chunks[0]);
+
+            let x_labels = day_labels(span);
+            let (wpm_bounds, wpm_labels) = y_axis(&wpm, 10., f64::MAX);
+            // the accuracy chart is too short to label every step
+            let (acc_bounds, _) = y_axis(&acc, 5., 100.);
+            let acc_labels: Vec<String> = acc_bounds.iter().map(|acc| acc.to_string()).collect();
+
+            // both charts have to start at the same column to line the days up
+            let label_width = wpm_labels
+                .iter()
+                .chain(&acc_labels)
+                .map(String::len)
+                .max()
+                .unwrap_or_default();
+
+            let wpm_datasets = vec![
+                Dataset::default()
+                    .name("runs")
+                    .marker(symbols::Marker::Dot)
+                    .style(Style::default().fg(RUNCOLOR))
+                    .graph_type(GraphType::Scatter)
+                    .data(&wpm),
+                Dataset::default()
+                    .name(format!("last {}", progress.window))
+                    .marker(symbols::Marker::Braille)
+                    .style(Style::default().fg(AVERAGECOLOR))
+                    .graph_type(GraphType::Line)
+                    .data(&wpm_average),
+                Dataset::default()
+                    .name("pb")
+                    .marker(symbols::Marker::Braille)
+                    .style(Style::default().fg(Color::Blue))
+                    .graph_type(GraphType::Line)
+                    .data(&pb_steps),
+                Dataset::default()
+                    .marker(symbols::Marker::Block)
+                    .style(Style::default().fg(PBCOLOR))
+                    .graph_type(GraphType::Scatter)
+                    .data(&bests),
+            ];
+            let wpm_chart = chart(
+                wpm_datasets,
+                "wpm",
+                span,
+                x_labels.clone(),
+                (wpm_bounds, wpm_labels),
+                label_width,
+            )
+            .hidden_legend_constraints((Constraint::Ratio(1, 3), Constraint::Ratio(1, 2)));
+            frame.render_widget(wpm_chart, chunks[1]);
+
+            let acc_datasets = vec![
+                Dataset::default()
+                    .marker(symbols::Marker::Dot)
+                    .style(Style::default().fg(ACCCOLOR))
+                    .graph_type(GraphType::Scatter)
+                    .data(&acc),
+                Dataset::default()
+                    .marker(symbols::Marker::Braille)
+                    .style(Style::default().fg(AVERAGECOLOR))
+                    .graph_type(GraphType::Line)
+                    .data(&acc_average),
+            ];
+            let acc_chart = chart(
+                acc_datasets,
+                "accuracy",
+                span,
+                x_labels,
+                (acc_bounds, acc_labels),
+                label_width,
+            );
+            frame.render_widget(acc_chart, chunks[2]);
+        })
+        .expect("drawing progress went oof");
+}
+
+/// the number of runs, the pb and how the average moved since the start
+fn summary<'a>(
+    progress: &'a Progress,
+    average: &[(f64, f64)],
+    bests: &[(f64, f64)],
+) -> Paragraph<'a> {
+    let first_average = average[progress.window.min(average.len()) - 1].1;
+    let last_average = average[average.len() - 1].1;
+    let gain = last_average - first_average;
+    let gain_color = if gain < 0. { Color::Red } else { ACCCOLOR };
+    let pb = bests.last().map_or(0., |&(_, wpm)| wpm);
+
+    let txt = vec![
+        Spans::from(vec![
+            Span::raw(format!("runs: {}  ", progress.runs.len())),
+            Span::raw("pb: "),
+            Span::styled(format!("{:.0}  ", pb), Style::default().fg(PBCOLOR)),
+            Span::raw(format!(
+                "first {} avg: {:.0}  last {} avg: {:.0} ",
+                progress.window, first_average, progress.window, last_average
+            )),
+            Span::styled(format!("{:+.1}", gain), Style::default().fg(gain_color)),
+        ]),
+        Spans::from(Span::styled(HINTS, Style::default().fg(Color::DarkGray))),
+    ];
+
+    let title = format!("{} since {}", progress.title, progress.runs[0].day);
+    Paragraph::new(txt).block(
+        Block::default()
+            .title(Span::styled(
+                title,
+                Style::default()
+                    .fg(Color::Blue)
+                    .add_modifier(Modifier::BOLD),
+            ))
+            .borders(Borders::ALL),
+    )
+}
+
+/// start, middle and end of the days since the first run
+fn day_labels(span: f64) -> Vec<String> {
+    [0., span / 2., span]
+        .iter()
+        .map(|day| match span < 10. {
+            true => format!("{:.1}", day),
+            false => format!("{:.0}", day),
+        })
+        .collect()
+}
+
+/// bounds and labels rounded to the step around the given points, up to the cap
+fn y_axis(points: &[(f64, f64)], step: f64, cap: f64) -> ([f64; 2], Vec<String>) {
+    let (min, max) = points
+        .iter()
+        .fold((f64::MAX, f64::MIN), |(min, max), &(_, y)| {
+            (min.min(y), max.max(y))
+        });
+    let lower = f64::max(0., min.div_euclid(step) * step - step);
+    let upper = f64::min(cap, max.div_euclid(step) * step + step);
+
+    let labels = (lower.div_euclid(step) as i32..=upper.div_euclid(step) as i32)
+        .map(|i| format!("{}", i as f64 * step))
+        .collect();
+    ([lower, upper], labels)
+}
+
+fn chart<'a>(
+    datasets: Vec<Dataset<'a>>,
+    title: &'a str,
+    span: f64,
+    x_labels: Vec<String>,
+    (bounds, labels): ([f64; 2], Vec<String>),
+    label_width: usize,
+) -> Chart<'a> {
+    let blue = Style::default().fg(Color::Blue);
+    let x_labels = x_labels
+        .into_iter()
+        .map(|label| Span::styled(label, blue))
+        .collect();
+    let y_labels = labels
+        .into_iter()
+        .map(|label| Span::styled(format!("{:>width$}", label, width = label_width), blue))
+        .collect();
+
+    Chart::new(datasets)
+        .block(Block::default().title(title).borders(Borders::ALL))
+        .x_axis(
+            Axis::default()
+                .title("days")
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0., span])
+                .labels(x_labels)
+                .labels_alignment(Alignment::Center),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds(bounds)
+                .labels(y_labels),
+        )
+}
@@ -0,0 +1,76 @@
+use super::typer::draw_lines;
+use crate::application::App;
+
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout},
+    style::{Color, Style},
+    text::{Span, Spans},
+    widgets::{Block, Borders, LineGauge, Paragraph},
+    Terminal,
+};
+
+const REPLAY_COLOR: Color = Color::Cyan;
+const HINTS: &str = "space pause, h/l scrub, ,/. step, 1/2/4 speed, esc back";
+
+pub fn draw_replay<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
+    terminal
+        .draw(|frame| {
+            let replay = &app.replay;
+
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(36), Constraint::Percentage(64)].as_ref())
+                .split(frame.size());
+
+            let top = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(3),
+                    Constraint::Length(1),
+                    Constraint::Min(0),
+                ])
+                .horizontal_margin(app.margin)
+                .split(chunks[0]);
+
+            let mut state = format!(
+                "{:.1}s / {:.1}s  {}x",
+                replay.at.as_secs_f64(),
+                replay.duration().as_secs_f64(),
+                replay.speed
+            );
+            if replay.paused {
+                state.push_str("  paused");
+            }
+
+            let up_txt = vec![
+                Spans::from(Span::styled(
+                    replay.title.as_str(),
+                    Style::default().fg(Color::DarkGray),
+                )),
+                Spans::from(vec![
+                    Span::raw(replay.wpm().round().to_string()),
+                    Span::styled(format!("  {}", state), Style::default().fg(REPLAY_COLOR)),
+                ]),
+                Spans::from(Span::styled(HINTS, Style::default().fg(Color::DarkGray))),
+            ];
+            let block = Paragraph::new(up_txt).block(Block::default().borders(Borders::NONE));
+            frame.render_widget(block, top[0]);
+
+            let ratio = if replay.duration().is_zero() {
+                1.
+            } else {
+                replay.at.as_secs_f64() / replay.duration().as_secs_f64()
+            };
+            let gauge = LineGauge::default()
+                .gauge_style(Style::default().fg(REPLAY_COLOR))
+                .label("")
+                .ratio(ratio.clamp(0., 1.));
+            frame.render_widget(gauge, top[1]);
+
+            let test = &replay.test;
+            let lines = [test.up.clone(), test.active.clone(), test.down.clone()];
+            draw_lines(frame, chunks[1], app.paragraph, test.cursor_x, lines);
+        })
+        .expect("drawing replay");
+}
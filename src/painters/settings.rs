@@ -59,7 +59,14 @@ pub fn draw_row_with_freq_and_len<B: Backend>(
 ) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .constraints(
+            [
+                Constraint::Percentage(33),
+                Constraint::Percentage(33),
+                Constraint::Percentage(34),
+            ]
+            .as_ref(),
+        )
         .split(rect);
 
     render_stateful_list(
@@ -79,6 +86,15 @@ pub fn draw_row_with_freq_and_len<B: Backend>(
         chunks[1],
         clrcode[&SetList::Frequency],
     );
+
+    render_stateful_list(
+        f,
+        &app.settings.mode_list.items,
+        &mut app.settings.mode_list.state,
+        "mode",
+        chunks[2],
+        clrcode[&SetList::Mode],
+    );
 }
 
 pub fn draw_row_with_words_and_mods<B: Backend>(
@@ -89,7 +105,14 @@ pub fn draw_row_with_words_and_mods<B: Backend>(
 ) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+        .constraints(
+            [
+                Constraint::Percentage(33),
+                Constraint::Percentage(33),
+                Constraint::Percentage(34),
+            ]
+            .as_ref(),
+        )
         .split(rect);
 
     render_stateful_list(
@@ -109,6 +132,15 @@ pub fn draw_row_with_words_and_mods<B: Backend>(
         chunks[1],
         clrcode[&SetList::Mods],
     );
+
+    render_stateful_list(
+        f,
+        &app.settings.rules_list.items,
+        &mut app.settings.rules_list.state,
+        "rules",
+        chunks[2],
+        clrcode[&SetList::Rules],
+    );
 }
 
 pub fn render_stateful_list<B: Backend>(
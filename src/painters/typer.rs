@@ -1,19 +1,16 @@
 use tui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout},
+    layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Style},
-    text::Spans,
+    text::{Span, Spans},
     widgets::{Block, Borders, Paragraph, Wrap},
-    Terminal,
+    Frame, Terminal,
 };
 
 use crate::application::App;
-use crate::Term;
 
-pub fn draw_test_and_update(terminal: &mut Term, app: &mut App) {
-    draw_test(terminal, app);
-    app.test.update_wpm_history();
-}
+const PACE_COLOR: Color = Color::Blue;
+const GHOST_COLOR: Color = Color::Magenta;
 
 pub fn draw_test<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
     terminal
@@ -26,40 +23,85 @@ pub fn draw_test<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) {
                 .split(frame.size());
 
             let wpm: String = test.calculate_wpm().round().to_string();
+            let mut wpm_line = vec![Span::raw(wpm)];
+
+            if let Some(gap) = test.pace_gap() {
+                let gap = if gap >= 0 {
+                    format!("  +{} chars ahead", gap)
+                } else {
+                    format!("  {} chars behind", gap)
+                };
+                wpm_line.push(Span::styled(gap, Style::default().fg(PACE_COLOR)));
+            }
+
+            if let Some(gap) = test.ghost_gap() {
+                wpm_line.push(Span::styled(
+                    format!("  ghost {:+}", gap),
+                    Style::default().fg(GHOST_COLOR),
+                ));
+            }
+
+            if test.is_paused() {
+                wpm_line.push(Span::styled(
+                    "  paused, any key resumes",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
 
             #[allow(unused_mut)]
             let mut dbg_info = String::new();
 
-            let up_txt = vec![Spans::from(wpm), Spans::from(dbg_info)];
+            let up_txt = vec![Spans::from(wpm_line), Spans::from(dbg_info)];
 
             let block = Paragraph::new(up_txt).block(Block::default().borders(Borders::NONE));
 
             frame.render_widget(block, chunks[0]);
 
-            let ghost_rect_width = (frame.size().width - app.paragraph) / 2;
-            let down_chunks = Layout::default()
-                .direction(Direction::Horizontal)
-                .constraints([Constraint::Length(ghost_rect_width), Constraint::Min(60)].as_ref())
-                .split(chunks[1]);
-
-            frame.set_cursor(
-                down_chunks[0].width + test.cursor_x - 1,
-                chunks[0].height + 1,
-            );
-
-            let txt = vec![
-                Spans::from(app.test.up.clone()),
-                Spans::from(app.test.active.clone()),
-                Spans::from(app.test.down.clone()),
+            let mut lines = [
+                app.test.up.clone(),
+                app.test.active.clone(),
+                app.test.down.clone(),
             ];
 
-            let paragraph = Paragraph::new(txt)
-                .block(Block::default().borders(Borders::NONE))
-                .style(Style::default().fg(Color::White))
-                // .alignment(Alignment::Center)
-                .wrap(Wrap { trim: false });
+            let carets = [
+                (app.test.pace_chars(), PACE_COLOR),
+                (app.test.ghost_chars(), GHOST_COLOR),
+            ];
+            for (chars, color) in carets {
+                if let Some((row, i)) = chars.and_then(|chars| app.test.locate_caret(chars)) {
+                    lines[row][i].style = lines[row][i].style.bg(color);
+                }
+            }
 
-            frame.render_widget(paragraph, down_chunks[1]);
+            draw_lines(frame, chunks[1], app.paragraph, app.test.cursor_x, lines);
         })
         .expect("drawing test went fine");
 }
+
+/// draws the lines of a test centered in the area
+/// with the cursor where the next key is expected
+pub(super) fn draw_lines<B: Backend>(
+    frame: &mut Frame<B>,
+    area: Rect,
+    paragraph: u16,
+    cursor_x: u16,
+    lines: [Vec<Span>; 3],
+) {
+    let ghost_rect_width = (frame.size().width - paragraph) / 2;
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(ghost_rect_width), Constraint::Min(60)].as_ref())
+        .split(area);
+
+    frame.set_cursor(chunks[0].width + cursor_x - 1, area.y + 1);
+
+    let txt: Vec<Spans> = lines.into_iter().map(Spans::from).collect();
+
+    let paragraph = Paragraph::new(txt)
+        .block(Block::default().borders(Borders::NONE))
+        .style(Style::default().fg(Color::White))
+        // .alignment(Alignment::Center)
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(paragraph, chunks[1]);
+}
@@ -0,0 +1,151 @@
+//! the long term progress of a test
+//! its runs are read once when the chart is opened, the series are worked out from them
+
+use crate::application::App;
+use crate::database::{get_progress, ProgressRun};
+use crate::settings::TypingTestConfig;
+use rusqlite::Connection;
+
+/// runs averaged by the moving average at first
+pub const MOVING_AVERAGE: usize = 10;
+const SECS_PER_DAY: f64 = 86_400.;
+
+pub struct Progress {
+    /// the test the runs belong to
+    pub title: String,
+    /// oldest first
+    pub runs: Vec<ProgressRun>,
+    /// runs averaged by the moving average
+    pub window: usize,
+    /// leaving the chart calls it
+    pub back: fn(&mut App),
+}
+
+impl Default for Progress {
+    fn default() -> Self {
+        Self {
+            title: String::new(),
+            runs: vec![],
+            window: MOVING_AVERAGE,
+            back: |app| app.change_to_settings(),
+        }
+    }
+}
+
+impl Progress {
+    pub fn load(conn: &Connection, ttc: &TypingTestConfig, back: fn(&mut App)) -> Self {
+        Self {
+            title: format!("{}", ttc),
+            runs: get_progress(conn, ttc).expect("reading the progress"),
+            back,
+            ..Self::default()
+        }
+    }
+
+    /// days since the first run
+    pub fn span(&self) -> f64 {
+        self.wpm().last().map_or(0., |&(day, _)| day)
+    }
+
+    /// wpm of every run against the days since the first one
+    pub fn wpm(&self) -> Vec<(f64, f64)> {
+        self.series(|run| run.wpm)
+    }
+
+    pub fn acc(&self) -> Vec<(f64, f64)> {
+        self.series(|run| run.acc)
+    }
+
+    fn series(&self, value: fn(&ProgressRun) -> f64) -> Vec<(f64, f64)> {
+        let first = self.runs.first().map_or(0, |run| run.date);
+        self.runs
+            .iter()
+            .map(|run| ((run.date - first) as f64 / SECS_PER_DAY, value(run)))
+            .collect()
+    }
+
+    pub fn widen(&mut self) {
+        self.window += 1;
+    }
+
+    pub fn narrow(&mut self) {
+        self.window = self.window.saturating_sub(1).max(1);
+    }
+}
+
+/// average of every point and the ones before it, window points at most
+pub fn moving_average(points: &[(f64, f64)], window: usize) -> Vec<(f64, f64)> {
+    points
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, _))| {
+            let averaged = &points[(i + 1).saturating_sub(window)..=i];
+            let sum: f64 = averaged.iter().map(|&(_, y)| y).sum();
+            (x, sum / averaged.len() as f64)
+        })
+        .collect()
+}
+
+/// points higher than every one before them
+pub fn personal_bests(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut best = f64::NEG_INFINITY;
+    points
+        .iter()
+        .filter(|&&(_, y)| {
+            let record = y > best;
+            best = best.max(y);
+            record
+        })
+        .copied()
+        .collect()
+}
+
+/// a line that holds every personal best until the next one, up to the given x
+pub fn steps(bests: &[(f64, f64)], until: f64) -> Vec<(f64, f64)> {
+    let mut line = vec![];
+    for (i, &(x, y)) in bests.iter().enumerate() {
+        if i > 0 {
+            line.push((x, bests[i - 1].1));
+        }
+        line.push((x, y));
+    }
+    if let Some(&(_, y)) = bests.last() {
+        line.push((until, y));
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_moving_average() {
+        let points = [(0., 10.), (1., 20.), (2., 60.), (3., 40.)];
+        assert_eq!(
+            moving_average(&points, 2),
+            vec![(0., 10.), (1., 15.), (2., 40.), (3., 50.)]
+        );
+        assert_eq!(moving_average(&points, 1), points.to_vec());
+        assert!(moving_average(&[], 3).is_empty());
+    }
+
+    #[test]
+    fn test_personal_bests() {
+        let points = [(0., 50.), (1., 40.), (2., 60.), (3., 60.), (4., 70.)];
+        let bests = personal_bests(&points);
+        assert_eq!(bests, vec![(0., 50.), (2., 60.), (4., 70.)]);
+        assert_eq!(
+            steps(&bests, 5.),
+            vec![
+                (0., 50.),
+                (2., 50.),
+                (2., 60.),
+                (4., 60.),
+                (4., 70.),
+                (5., 70.)
+            ]
+        );
+        assert!(steps(&[], 5.).is_empty());
+    }
+}
@@ -0,0 +1,353 @@
+//! replays of finished runs
+//! the run is typed out again on a fresh test following its keystroke log
+
+use crate::application::App;
+use crate::database::keystrokes::Recording;
+use crate::langs;
+use crate::settings::TypingTestConfig;
+use crate::typer::{Keystroke, TestColors, TestState, Timeline, BACKSPACE, DELETE_WORD};
+use std::time::{Duration, Instant};
+use tui::text::Span;
+
+pub struct Replay<'t> {
+    pub test: TestState<'t>,
+    pub keystrokes: Vec<Keystroke>,
+    /// keystrokes already applied to the test
+    pub shown: usize,
+    /// how far into the run the replay is
+    pub at: Duration,
+    /// multiplies the pace of the run
+    pub speed: u32,
+    pub paused: bool,
+    pub title: String,
+    /// leaves the replay for the screen it was opened from
+    pub back: fn(&mut App),
+
+    lines: Vec<Vec<Span<'t>>>,
+    config: TypingTestConfig,
+    last_tick: Instant,
+}
+
+impl Default for Replay<'_> {
+    fn default() -> Self {
+        Self {
+            test: TestState::default(),
+            keystrokes: vec![],
+            shown: 0,
+            at: Duration::ZERO,
+            speed: 1,
+            paused: false,
+            title: String::new(),
+            back: |app| app.change_to_post(),
+            lines: vec![],
+            config: TypingTestConfig::default(),
+            last_tick: Instant::now(),
+        }
+    }
+}
+
+impl<'t> Replay<'t> {
+    /// replays the run that was just typed on the given test
+    pub fn from_test(test: &TestState<'t>, config: &TypingTestConfig, back: fn(&mut App)) -> Self {
+        let title = format!("{} | {} wpm", config, config.test_summary.wpm.round());
+        Self::new(
+            test.pristine.clone(),
+            test.keystrokes.clone(),
+            replay_config(config),
+            test.colors,
+            title,
+            back,
+        )
+    }
+
+    /// replays a run saved in the database
+    pub fn from_recording(
+        run_id: i64,
+        recording: Recording,
+        colors: TestColors,
+        back: fn(&mut App),
+    ) -> Self {
+        let title = format!(
+            "run {} | {} | {} wpm",
+            run_id,
+            recording.name,
+            recording.wpm.round()
+        );
+        let config = TypingTestConfig {
+            skip_indent: recording.skip_indent,
+            error_policy: recording.error_policy,
+            ..TypingTestConfig::default()
+        };
+        Self::new(
+            langs::text_to_lines(&recording.text, &colors),
+            recording.keystrokes,
+            config,
+            colors,
+            title,
+            back,
+        )
+    }
+
+    fn new(
+        lines: Vec<Vec<Span<'t>>>,
+        keystrokes: Vec<Keystroke>,
+        config: TypingTestConfig,
+        colors: TestColors,
+        title: String,
+        back: fn(&mut App),
+    ) -> Self {
+        let mut replay = Self {
+            test: TestState::with_colors(colors),
+            keystrokes,
+            title,
+            back,
+            lines,
+            config,
+            ..Self::default()
+        };
+        replay.rewind();
+        replay
+    }
+
+    /// length of the run
+    pub fn duration(&self) -> Duration {
+        self.keystrokes
+            .last()
+            .map_or(Duration::ZERO, |key| Duration::from_millis(key.ms as u64))
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.at >= self.duration()
+    }
+
+    /// wpm the run had at this point
+    pub fn wpm(&self) -> f64 {
+        if self.at.is_zero() {
+            return 0.;
+        }
+        self.test.wpm_after(self.at)
+    }
+
+    /// back to the state before the first keystroke
+    fn rewind(&mut self) {
+        self.test.load(&self.config, self.lines.clone());
+        self.shown = 0;
+        self.at = Duration::ZERO;
+    }
+
+    fn press(&mut self, key: Keystroke) {
+        press(&mut self.test, key);
+        self.shown += 1;
+    }
+
+    /// presses every key that is due
+    fn catch_up(&mut self) {
+        while let Some(&key) = self.keystrokes.get(self.shown) {
+            if Duration::from_millis(key.ms as u64) > self.at {
+                break;
+            }
+            self.press(key);
+        }
+    }
+
+    /// moves the replay to the given point of the run
+    pub fn seek(&mut self, at: Duration) {
+        let at = at.min(self.duration());
+        if at < self.at {
+            self.rewind();
+        }
+        self.at = at;
+        self.catch_up();
+    }
+
+    pub fn scrub_forward(&mut self, by: Duration) {
+        self.seek(self.at + by);
+    }
+
+    pub fn scrub_back(&mut self, by: Duration) {
+        self.seek(self.at.saturating_sub(by));
+    }
+
+    /// pauses right after the next keystroke
+    pub fn step_forward(&mut self) {
+        self.paused = true;
+        if let Some(key) = self.keystrokes.get(self.shown) {
+            self.seek(Duration::from_millis(key.ms as u64));
+        }
+    }
+
+    /// pauses right after the keystroke before the last one shown
+    pub fn step_back(&mut self) {
+        self.paused = true;
+        let target = self.shown.saturating_sub(1);
+        self.rewind();
+
+        while self.shown < target {
+            self.press(self.keystrokes[self.shown]);
+        }
+        if let Some(key) = target.checked_sub(1).map(|i| self.keystrokes[i]) {
+            self.at = Duration::from_millis(key.ms as u64);
+        }
+    }
+
+    /// a finished replay starts over
+    pub fn toggle_pause(&mut self) {
+        self.last_tick = Instant::now();
+        if self.is_over() {
+            self.seek(Duration::ZERO);
+            self.paused = false;
+        } else {
+            self.paused = !self.paused;
+        }
+    }
+
+    pub fn into_timeline(self) -> Timeline {
+        timeline(self.lines, &self.keystrokes, &self.config)
+    }
+
+    /// moves the replay along with the clock
+    /// returns how long it takes for the next keystroke to come up
+    pub fn advance(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        let passed = now.duration_since(self.last_tick);
+        self.last_tick = now;
+
+        if self.paused || self.is_over() {
+            return None;
+        }
+
+        self.at = (self.at + passed * self.speed).min(self.duration());
+        self.catch_up();
+
+        let next = Duration::from_millis(self.keystrokes.get(self.shown)?.ms as u64);
+        Some((next - self.at) / self.speed)
+    }
+}
+
+/// only the rules that change where the keys land are kept
+/// so the replay can't fail or pause halfway through
+fn replay_config(config: &TypingTestConfig) -> TypingTestConfig {
+    TypingTestConfig {
+        skip_indent: config.skip_indent,
+        error_policy: config.error_policy,
+        ..TypingTestConfig::default()
+    }
+}
+
+/// presses the key the same way the key handler did
+fn press(test: &mut TestState, key: Keystroke) {
+    match key.typed {
+        BACKSPACE => test.undo_char(),
+        DELETE_WORD => {
+            test.undo_word();
+            test.set_next_char();
+        }
+        c => {
+            test.on_char(c);
+        }
+    }
+}
+
+/// (ms, chars typed) after every keystroke of a run typed on the given lines
+/// the rolling wpm is taken from it and ghosts move along it
+pub fn timeline(
+    lines: Vec<Vec<Span>>,
+    keystrokes: &[Keystroke],
+    config: &TypingTestConfig,
+) -> Timeline {
+    let mut test = TestState::default();
+    test.load(&replay_config(config), lines);
+    keystrokes
+        .iter()
+        .map(|&key| {
+            press(&mut test, key);
+            (key.ms, test.typed_chars() as u32)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn replay_of_typed_test() -> (Replay<'static>, Vec<String>) {
+        let config = TypingTestConfig::default();
+        let mut test = TestState::default();
+        // no word ends where the wrong chars are typed
+        let lines = langs::to_code_span("quick brown foxes", &test.colors);
+        test.load(&config, lines);
+
+        for i in 0..12 {
+            test.on_char(test.current_char);
+            if i % 4 == 3 {
+                test.on_char('ź');
+                test.undo_char();
+            }
+        }
+        // the log comes out of the real clock, spread it out evenly
+        for (i, key) in test.keystrokes.iter_mut().enumerate() {
+            key.ms = i as u32 * 100;
+        }
+
+        let typed = test.active.iter().map(|s| s.content.to_string()).collect();
+        let replay = Replay::from_test(&test, &config, |app| app.change_to_post());
+        (replay, typed)
+    }
+
+    #[test]
+    fn test_replay_ends_where_the_run_did() {
+        let (mut replay, typed) = replay_of_typed_test();
+        assert_eq!(replay.shown, 0);
+
+        replay.seek(replay.duration());
+        assert!(replay.is_over());
+        assert_eq!(replay.shown, replay.keystrokes.len());
+        let replayed: Vec<String> = replay
+            .test
+            .active
+            .iter()
+            .map(|s| s.content.to_string())
+            .collect();
+        assert_eq!(replayed, typed);
+        assert_eq!(replay.test.summarize().mistakes, 0);
+    }
+
+    #[test]
+    fn test_replay_scrub_and_step() {
+        let (mut replay, _) = replay_of_typed_test();
+
+        replay.scrub_forward(Duration::from_millis(450));
+        assert_eq!(replay.shown, 5);
+        // wrong char typed as the fourth one isn't taken back yet
+        assert_eq!(replay.test.summarize().mistakes, 1);
+
+        replay.step_forward();
+        assert_eq!(replay.shown, 6);
+        assert_eq!(replay.test.summarize().mistakes, 0);
+        assert!(replay.paused);
+
+        replay.step_back();
+        assert_eq!(replay.shown, 5);
+        assert_eq!(replay.at, Duration::from_millis(400));
+        assert_eq!(replay.test.summarize().mistakes, 1);
+
+        replay.scrub_back(Duration::from_secs(10));
+        assert_eq!(replay.shown, 1);
+        assert_eq!(replay.test.done, 1);
+    }
+
+    #[test]
+    fn test_replay_timeline() {
+        let (replay, _) = replay_of_typed_test();
+        let keys = replay.keystrokes.len();
+        let timeline = replay.into_timeline();
+        assert_eq!(timeline.len(), keys);
+
+        // corrections take the caret back
+        assert_eq!(
+            timeline[..6],
+            [(0, 1), (100, 2), (200, 3), (300, 4), (400, 5), (500, 4)]
+        );
+        assert_eq!(timeline.last(), Some(&(1700, 12)));
+    }
+}
@@ -1,15 +1,26 @@
 use crate::database::{self, RunHistoryDatbase};
+use crate::langs;
+use crate::langs::drill::DRILLED_NGRAMS;
+use crate::langs::lesson::Lesson;
+use crate::langs::quotes::{self, Quote};
+use crate::langs::text::CustomText;
+use crate::replay;
+use crate::stats::{KeyStats, LetterConfidence, NgramStats, WordReport};
 use crate::storage;
-use crate::utils::{count_lines_from_path, termprep, StatefulList};
+use crate::typer::{TestColors, TestState, Timeline};
+use crate::utils::{count_lines_from_path, stable_hash, termprep, StatefulList};
 use crate::vec_of_strings;
 use rusqlite::Connection;
 use std::collections::{HashMap, HashSet};
 use std::fmt;
 use std::hash::Hash;
 use std::path::PathBuf;
+use std::time::Duration;
 use tui::style::Color;
 
 pub const SCRIPT_SIGN: &str = "#!";
+pub const QUOTE_SIGN: &str = "\"\"";
+pub const CODE_SIGN: &str = "{}";
 
 use bimap::BiMap;
 use lazy_static::lazy_static;
@@ -24,6 +35,76 @@ lazy_static! {
         ("numbers", TestMod::Numbers),
         ("symbols", TestMod::Symbols),
         ("capitalization", TestMod::Capitalization),
+        ("weak letters", TestMod::WeakLetters),
+    ]
+    .iter()
+    .copied()
+    .collect();
+}
+
+lazy_static! {
+    pub static ref TEST_MODES: BiMap<&'static str, TestVariant> = [
+        ("words", TestVariant::Standard),
+        ("time", TestVariant::Timed),
+        ("lesson", TestVariant::Lesson),
+        ("drill", TestVariant::Drill),
+    ]
+    .iter()
+    .copied()
+    .collect();
+}
+
+lazy_static! {
+    pub static ref QUOTE_LENGTHS: BiMap<&'static str, QuoteLength> = [
+        ("all", QuoteLength::All),
+        ("short", QuoteLength::Short),
+        ("medium", QuoteLength::Medium),
+        ("long", QuoteLength::Long),
+        ("thicc", QuoteLength::Thicc),
+    ]
+    .iter()
+    .copied()
+    .collect();
+}
+
+lazy_static! {
+    pub static ref ERROR_POLICIES: BiMap<&'static str, ErrorPolicy> = [
+        ("free", ErrorPolicy::Free),
+        ("stop on letter", ErrorPolicy::StopOnLetter),
+        ("stop on word", ErrorPolicy::StopOnWord),
+    ]
+    .iter()
+    .copied()
+    .collect();
+}
+
+lazy_static! {
+    pub static ref FAIL_POLICIES: BiMap<&'static str, FailPolicy> = [
+        ("off", FailPolicy::Off),
+        ("sudden death", FailPolicy::SuddenDeath),
+        ("min accuracy", FailPolicy::MinAccuracy),
+    ]
+    .iter()
+    .copied()
+    .collect();
+}
+
+lazy_static! {
+    pub static ref IDLE_POLICIES: BiMap<&'static str, IdlePolicy> = [
+        ("pause", IdlePolicy::Pause),
+        ("abort", IdlePolicy::Abort),
+        ("off", IdlePolicy::Off),
+    ]
+    .iter()
+    .copied()
+    .collect();
+}
+
+lazy_static! {
+    pub static ref BACKSPACE_POLICIES: BiMap<&'static str, BackspacePolicy> = [
+        ("on", BackspacePolicy::On),
+        ("within word", BackspacePolicy::WithinWord),
+        ("off", BackspacePolicy::Off),
     ]
     .iter()
     .copied()
@@ -36,6 +117,7 @@ lazy_static! {
         (0b00000010, TestMod::Numbers),
         (0b00000100, TestMod::Symbols),
         (0b00001000, TestMod::Capitalization),
+        (0b00010000, TestMod::WeakLetters),
     ]
     .iter()
     .copied()
@@ -49,27 +131,250 @@ pub fn is_script(text: &str) -> bool {
     &text[..2] == SCRIPT_SIGN
 }
 
+pub fn is_quote(text: &str) -> bool {
+    text.starts_with(QUOTE_SIGN)
+}
+
+pub fn is_code(text: &str) -> bool {
+    text.starts_with(CODE_SIGN)
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SetList {
     Length,
     Frequency,
     Test,
     Mods,
+    Mode,
+    Rules,
     Nil,
 }
 
-#[allow(dead_code)]
+/// discriminants are saved to the database
+/// so they shouldn't ever change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum TestVariant {
-    Standard,
-    Script,
+    #[default]
+    Standard = 0,
+    Script = 1,
+    Timed = 2,
+    Quote = 3,
+    Code = 4,
+    // text given through the command line
+    Text = 5,
+    // word list limited to the letters unlocked so far
+    Lesson = 6,
+    // words with the bigrams and trigrams that need work
+    Drill = 7,
+}
+
+impl TestVariant {
+    /// variants that draw their words from a word list
+    /// and thus care about length, word pool and mods
+    pub fn uses_word_list(self) -> bool {
+        matches!(
+            self,
+            Self::Standard | Self::Timed | Self::Lesson | Self::Drill
+        )
+    }
+}
+
+/// how the test reacts to wrong keys
+/// discriminants are saved to the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ErrorPolicy {
+    // mistakes are marked and the test moves on
+    #[default]
+    Free = 0,
+    // the test waits for the correct key
+    StopOnLetter = 1,
+    // space isn't accepted until the word is correct
+    StopOnWord = 2,
 }
 
+impl ErrorPolicy {
+    /// policy of a saved run, unknown values fall back to free
+    pub fn from_discriminant(discriminant: u8) -> Self {
+        match discriminant {
+            1 => Self::StopOnLetter,
+            2 => Self::StopOnWord,
+            _ => Self::Free,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::Free => Self::StopOnLetter,
+            Self::StopOnLetter => Self::StopOnWord,
+            Self::StopOnWord => Self::Free,
+        }
+    }
+}
+
+impl fmt::Display for ErrorPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = ERROR_POLICIES
+            .get_by_right(self)
+            .expect("ERROR_POLICIES should cover every policy");
+        write!(f, "{}", name)
+    }
+}
+
+/// when the test gives up on the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FailPolicy {
+    #[default]
+    Off,
+    // fails on the first mistake left behind
+    SuddenDeath,
+    // fails once accuracy drops below the threshold
+    MinAccuracy,
+}
+
+impl FailPolicy {
+    fn next(self) -> Self {
+        match self {
+            Self::Off => Self::SuddenDeath,
+            Self::SuddenDeath => Self::MinAccuracy,
+            Self::MinAccuracy => Self::Off,
+        }
+    }
+}
+
+impl fmt::Display for FailPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = FAIL_POLICIES
+            .get_by_right(self)
+            .expect("FAIL_POLICIES should cover every policy");
+        write!(f, "{}", name)
+    }
+}
+
+/// what happens once the typist walks away mid test
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum IdlePolicy {
+    // the clock stops from the last key press until typing resumes
+    #[default]
+    Pause,
+    // the run is thrown away and a new test starts
+    Abort,
+    Off,
+}
+
+/// what backspace is allowed to take back
+/// discriminants are saved to the database
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BackspacePolicy {
+    #[default]
+    On = 0,
+    // only the word being typed can be corrected
+    WithinWord = 1,
+    // confidence mode, no corrections at all
+    Off = 2,
+}
+
+impl BackspacePolicy {
+    /// policy of a saved run, unknown values fall back to on
+    pub fn from_discriminant(discriminant: u8) -> Self {
+        match discriminant {
+            1 => Self::WithinWord,
+            2 => Self::Off,
+            _ => Self::On,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Self::On => Self::WithinWord,
+            Self::WithinWord => Self::Off,
+            Self::Off => Self::On,
+        }
+    }
+}
+
+impl fmt::Display for BackspacePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = BACKSPACE_POLICIES
+            .get_by_right(self)
+            .expect("BACKSPACE_POLICIES should cover every policy");
+        write!(f, "{}", name)
+    }
+}
+
+/// fixed targets the pace rule cycles through
+const PACE_PRESETS: [u16; 5] = [40, 60, 80, 100, 120];
+/// runs the average pace is taken from
+pub const PACE_AVERAGE_RUNS: usize = 10;
+/// runs the cumulative heatmap of the post screen is made of
+pub const HEATMAP_RUNS: usize = 10;
+/// runs the weakest n-grams of drills are looked for in
+const DRILL_RUNS: usize = 20;
+/// words listed in each category of the post screen report
+const REPORTED_WORDS: usize = 5;
+
+/// speed of the pace caret
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Pace {
+    #[default]
+    Off,
+    Wpm(u16),
+    // average of the last PACE_AVERAGE_RUNS runs of the test
+    Average,
+    // personal best of the test
+    Best,
+}
+
+impl Pace {
+    fn next(self) -> Self {
+        match self {
+            Self::Off => Self::Wpm(PACE_PRESETS[0]),
+            Self::Wpm(wpm) => PACE_PRESETS
+                .iter()
+                .find(|&&preset| preset > wpm)
+                .map_or(Self::Average, |&preset| Self::Wpm(preset)),
+            Self::Average => Self::Best,
+            Self::Best => Self::Off,
+        }
+    }
+}
+
+impl fmt::Display for Pace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => write!(f, "off"),
+            Self::Wpm(wpm) => write!(f, "{} wpm", wpm),
+            Self::Average => write!(f, "average"),
+            Self::Best => write!(f, "pb"),
+        }
+    }
+}
+
+/// rules shown in the rules list, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TestRule {
+    Errors,
+    Fail,
+    Backspace,
+    Pace,
+    Ghost,
+}
+
+const RULES: [TestRule; 5] = [
+    TestRule::Errors,
+    TestRule::Fail,
+    TestRule::Backspace,
+    TestRule::Pace,
+    TestRule::Ghost,
+];
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum TestMod {
     Punctuation,
     Numbers,
     Symbols,
     Capitalization,
+    // words lean towards the letters with the lowest confidence
+    WeakLetters,
 }
 
 impl TestMod {
@@ -79,6 +384,7 @@ impl TestMod {
             0b00000010 => TestMod::Numbers,
             0b00000100 => TestMod::Symbols,
             0b00001000 => TestMod::Capitalization,
+            0b00010000 => TestMod::WeakLetters,
             _ => unreachable!(),
         }
     }
@@ -91,10 +397,42 @@ impl fmt::Display for TestMod {
             Self::Numbers => write!(f, "17"),
             Self::Symbols => write!(f, "#$"),
             Self::Capitalization => write!(f, "Aa"),
+            Self::WeakLetters => write!(f, "weak"),
         }
     }
 }
 
+/// length filter of quote tests, measured in characters
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuoteLength {
+    All,
+    Short,
+    Medium,
+    Long,
+    Thicc,
+}
+
+impl QuoteLength {
+    pub fn fits(self, chars: usize) -> bool {
+        match self {
+            Self::All => true,
+            Self::Short => chars <= 100,
+            Self::Medium => (101..=300).contains(&chars),
+            Self::Long => (301..=600).contains(&chars),
+            Self::Thicc => chars > 600,
+        }
+    }
+}
+
+impl fmt::Display for QuoteLength {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = QUOTE_LENGTHS
+            .get_by_right(self)
+            .expect("QUOTE_LENGTHS should cover every length");
+        write!(f, "{}", name)
+    }
+}
+
 pub fn decode_test_mod_bitflags(bitflag: u8) -> HashSet<TestMod> {
     let mut test_mods: HashSet<TestMod> = HashSet::new();
 
@@ -109,9 +447,26 @@ pub fn decode_test_mod_bitflags(bitflag: u8) -> HashSet<TestMod> {
 
 pub struct TestSummary {
     pub correct_chars: usize,
+    // uncorrected, left behind in the text
     pub mistakes: usize,
+    // wrong keys that were taken back
+    pub corrected: usize,
     pub wpm: f64,
+    // every typed char counts, mistakes and corrected ones included
+    pub raw_wpm: f64,
+    // correct chars per minute
+    pub cpm: f64,
+    // percent, 100 minus the coefficient of variation of the wpm samples
+    pub consistency: f64,
+    // seconds the clock was stopped for, by hand or when idle
+    pub paused: f64,
+    // wpm with the pauses counted in
+    pub wall_wpm: f64,
     pub acc: f64,
+    // failed runs never count as records
+    pub failed: bool,
+    // 0 for fresh text, otherwise the how manieth retry of it the run was
+    pub retries: u32,
 }
 
 impl Default for TestSummary {
@@ -119,8 +474,40 @@ impl Default for TestSummary {
         Self {
             correct_chars: 0,
             mistakes: 0,
+            corrected: 0,
             wpm: 0.,
+            raw_wpm: 0.,
+            cpm: 0.,
+            consistency: 0.,
+            paused: 0.,
+            wall_wpm: 0.,
             acc: 0.,
+            failed: false,
+            retries: 0,
+        }
+    }
+}
+
+/// wpm series shown on the chart of the post screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChartSeries {
+    // wpm since the start
+    pub average: bool,
+    // wpm of the last couple of seconds
+    pub rolling: bool,
+    // wpm of every word
+    pub burst: bool,
+    // marks where the mistakes happened
+    pub mistakes: bool,
+}
+
+impl Default for ChartSeries {
+    fn default() -> Self {
+        Self {
+            average: true,
+            rolling: true,
+            burst: true,
+            mistakes: true,
         }
     }
 }
@@ -128,16 +515,34 @@ impl Default for TestSummary {
 #[derive(Default)]
 pub struct PostBox {
     pub cached_historic_wpm: f64,
+    pub key_stats: KeyStats,
+    // key stats of the last HEATMAP_RUNS runs
+    pub recent_key_stats: KeyStats,
+    // the heatmap shows the recent runs instead of the last one
+    pub cumulative: bool,
+    pub words: WordReport,
+    // letter the last lesson run unlocked
+    pub unlocked: Option<char>,
+    pub series: ChartSeries,
+    /// (ms, chars typed) of the last run, the rolling wpm is taken from it
+    pub timeline: Timeline,
 }
 
 /// Basically a dupe of some of the info of ttc
 /// but allows me to be more flexible in the future
 /// when it comes to caching test info
-#[derive(PartialEq, Eq, Hash, Debug)]
+#[derive(PartialEq, Eq, Hash, Debug, Default, Clone)]
 pub struct TestIdentity {
+    pub variant: TestVariant,
     pub length: usize,
     pub word_pool: usize,
     pub mods: u8,
+    // seconds, timed tests only
+    pub duration: u64,
+    // quote id, quote tests only
+    pub text_id: usize,
+    pub error_policy: ErrorPolicy,
+    pub backspace: BackspacePolicy,
 }
 
 /// This stuct contains information about
@@ -150,8 +555,36 @@ pub struct TypingTestConfig {
     pub name: String,
     pub variant: TestVariant,
     pub length: usize,
+    // seconds, used by timed tests instead of length
+    pub duration: u64,
     pub word_pool: usize,
     pub mods: HashSet<TestMod>,
+    pub quote_length: QuoteLength,
+    // quote typed in the current test
+    pub quote: Option<Quote>,
+    // text given through the command line
+    pub text: Option<CustomText>,
+    // progress of the lesson of the word list
+    pub lesson: Option<Lesson>,
+    // n-grams given in the config, drills pick the weakest ones when empty
+    pub ngrams: Vec<String>,
+    // n-grams of the current drill
+    pub drill: Vec<String>,
+    // whether code tests skip the indentation or make you type it
+    pub skip_indent: bool,
+    pub error_policy: ErrorPolicy,
+    pub backspace: BackspacePolicy,
+    pub fail_policy: FailPolicy,
+    // percent, used by FailPolicy::MinAccuracy
+    pub min_acc: u8,
+    pub idle_policy: IdlePolicy,
+    // seconds without a key press that count as idle
+    pub idle_secs: u64,
+    pub pace: Pace,
+    // races the best run of the test
+    pub ghost: bool,
+    // what the weak letters mod knows about the typist
+    pub confidence: LetterConfidence,
 
     // summary
     pub test_summary: TestSummary,
@@ -159,22 +592,69 @@ pub struct TypingTestConfig {
 
 impl fmt::Display for TypingTestConfig {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut mods = String::new();
+        if !self.mods.is_empty() {
+            mods.push('+')
+        }
+        for test_mod in &self.mods {
+            mods.push_str(&format!(" {}", test_mod));
+        }
+
         match self.variant {
-            TestVariant::Standard => {
-                let mut mods = String::new();
-                if !self.mods.is_empty() {
-                    mods.push('+')
-                }
-                for test_mod in &self.mods {
-                    mods.push_str(&format!(" {}", test_mod));
-                }
-                write!(
+            TestVariant::Standard => write!(
+                f,
+                "{}: {}/{} {}",
+                self.name, self.length, self.word_pool, mods
+            ),
+            TestVariant::Timed => write!(
+                f,
+                "{}: {}s/{} {}",
+                self.name, self.duration, self.word_pool, mods
+            ),
+            TestVariant::Quote => write!(f, "{} quotes: {}", self.name, self.quote_length),
+            TestVariant::Text => match &self.text {
+                Some(text) if text.parts.len() > 1 => write!(
+                    f,
+                    "{}: part {}/{}",
+                    self.name,
+                    text.part + 1,
+                    text.parts.len()
+                ),
+                _ => write!(f, "{}", self.name),
+            },
+            TestVariant::Lesson => match &self.lesson {
+                Some(lesson) => write!(
                     f,
-                    "{}: {}/{} {}",
+                    "{} lesson: {}",
+                    self.name,
+                    lesson.unlocked_letters().iter().collect::<String>()
+                ),
+                None => write!(f, "{} lesson", self.name),
+            },
+            TestVariant::Drill => {
+                let drill = format!(
+                    "{} drill: {}/{} {}",
                     self.name, self.length, self.word_pool, mods
-                )
+                );
+                write!(f, "{}", drill.trim_end())?;
+                match self.drill.is_empty() {
+                    true => Ok(()),
+                    false => write!(f, " | {}", self.drill.join(" ")),
+                }
             }
             _ => write!(f, "{}", self.name),
+        }?;
+
+        if self.error_policy != ErrorPolicy::Free {
+            write!(f, " | {}", self.error_policy)?;
+        }
+        if self.backspace != BackspacePolicy::On {
+            write!(f, " | backspace {}", self.backspace)?;
+        }
+        match self.fail_policy {
+            FailPolicy::Off => Ok(()),
+            FailPolicy::SuddenDeath => write!(f, " | sudden death"),
+            FailPolicy::MinAccuracy => write!(f, " | min acc {}%", self.min_acc),
         }
     }
 }
@@ -186,8 +666,25 @@ impl Default for TypingTestConfig {
             name: String::from("english"),
             variant: TestVariant::Standard,
             length: 25,
+            duration: 30,
             word_pool: 5000,
             mods: HashSet::default(),
+            quote_length: QuoteLength::All,
+            quote: None,
+            text: None,
+            lesson: None,
+            ngrams: vec![],
+            drill: vec![],
+            skip_indent: true,
+            error_policy: ErrorPolicy::Free,
+            backspace: BackspacePolicy::On,
+            fail_policy: FailPolicy::Off,
+            min_acc: 90,
+            idle_policy: IdlePolicy::Pause,
+            idle_secs: 10,
+            pace: Pace::Off,
+            ghost: false,
+            confidence: LetterConfidence::default(),
             test_summary: TestSummary::default(),
         }
     }
@@ -220,17 +717,120 @@ impl TypingTestConfig {
 
     // TODO rename this XD
     pub fn gib_identity(&self) -> TestIdentity {
+        let identity = match self.variant {
+            TestVariant::Standard => TestIdentity {
+                variant: self.variant,
+                length: self.length,
+                word_pool: self.word_pool,
+                mods: database::encode_test_mod_bitflag(&self.mods),
+                ..TestIdentity::default()
+            },
+            // every set of drilled n-grams has its own records, whatever their order
+            TestVariant::Drill => TestIdentity {
+                variant: self.variant,
+                length: self.length,
+                word_pool: self.word_pool,
+                mods: database::encode_test_mod_bitflag(&self.mods),
+                text_id: {
+                    let mut drill = self.drill.clone();
+                    drill.sort();
+                    stable_hash(&drill.join(" ")) as usize
+                },
+                ..TestIdentity::default()
+            },
+            TestVariant::Timed => TestIdentity {
+                variant: self.variant,
+                word_pool: self.word_pool,
+                mods: database::encode_test_mod_bitflag(&self.mods),
+                duration: self.duration,
+                ..TestIdentity::default()
+            },
+            TestVariant::Quote => TestIdentity {
+                variant: self.variant,
+                text_id: self.quote.as_ref().map_or(0, |quote| quote.id),
+                ..TestIdentity::default()
+            },
+            // practice texts are made anew from every run, each keeps its own records
+            TestVariant::Text if self.name == PRACTICE => TestIdentity {
+                variant: self.variant,
+                text_id: self
+                    .text
+                    .as_ref()
+                    .map_or(0, |text| stable_hash(text.current()) as usize),
+                ..TestIdentity::default()
+            },
+            TestVariant::Text => TestIdentity {
+                variant: self.variant,
+                text_id: self.text.as_ref().map_or(0, |text| text.part),
+                ..TestIdentity::default()
+            },
+            // every set of unlocked letters has its own records
+            TestVariant::Lesson => TestIdentity {
+                variant: self.variant,
+                length: self.length,
+                word_pool: self.word_pool,
+                text_id: self.lesson.as_ref().map_or(0, |lesson| lesson.unlocked),
+                ..TestIdentity::default()
+            },
+            TestVariant::Script | TestVariant::Code => TestIdentity {
+                variant: self.variant,
+                ..TestIdentity::default()
+            },
+        };
+
         TestIdentity {
-            length: self.length,
-            word_pool: self.word_pool,
-            mods: database::encode_test_mod_bitflag(&self.mods),
+            error_policy: self.error_policy,
+            backspace: self.backspace,
+            ..identity
+        }
+    }
+
+    /// picks the text for tests that type a particular text
+    /// has to be called before the test is prepared
+    pub fn pick_text(&mut self) {
+        self.quote = match self.variant {
+            TestVariant::Quote => {
+                match quotes::pick_quote(self.get_quotes_file_path(), self.quote_length) {
+                    Ok(quote) => Some(quote),
+                    Err(err) => {
+                        termprep::panic_with_friendly_message(&format!(
+                            "{:?}\ncouldn't read this quote collection: {}",
+                            self.get_quotes_file_path(),
+                            err
+                        ));
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+    }
+
+    /// custom text moves on to its next part
+    pub fn next_part(&mut self) {
+        if let Some(text) = &mut self.text {
+            text.advance();
+        }
+    }
+
+    /// how long the test lasts, only timed tests have a limit
+    pub fn time_limit(&self) -> Option<Duration> {
+        match self.variant {
+            TestVariant::Timed => Some(Duration::from_secs(self.duration)),
+            _ => None,
         }
     }
 
     fn get_file_path(&self) -> PathBuf {
         match self.variant {
-            TestVariant::Standard => self.get_words_file_path(),
+            TestVariant::Standard
+            | TestVariant::Timed
+            | TestVariant::Lesson
+            | TestVariant::Drill => self.get_words_file_path(),
             TestVariant::Script => self.get_scripts_file_path(),
+            TestVariant::Quote => self.get_quotes_file_path(),
+            TestVariant::Code => self.get_code_file_path(),
+            TestVariant::Text => unreachable!("custom text doesn't come from the storage"),
         }
     }
 
@@ -241,6 +841,14 @@ impl TypingTestConfig {
     pub fn get_scripts_file_path(&self) -> PathBuf {
         storage::get_storage_dir().join("scripts").join(&self.name)
     }
+
+    pub fn get_quotes_file_path(&self) -> PathBuf {
+        storage::get_quotes_path(&self.name)
+    }
+
+    pub fn get_code_file_path(&self) -> PathBuf {
+        storage::get_code_path(&self.name)
+    }
 }
 
 pub struct SettingsColors {
@@ -259,6 +867,9 @@ impl Default for SettingsColors {
 // Option feels more clean than f64::NAN
 type InfoCache = HashMap<String, (usize, HashMap<TestIdentity, Option<f64>>)>;
 type ScriptCache = HashMap<String, Option<f64>>;
+// HM<(test.name, TestIdentity), historic_max_wpm>
+// filled lazily as quotes change every run
+type TextCache = HashMap<(String, TestIdentity), Option<f64>>;
 
 pub struct Settings {
     pub hovered: SetList,
@@ -266,26 +877,44 @@ pub struct Settings {
     pub colors: SettingsColors,
 
     pub test_cfg: TypingTestConfig,
+    // variant picked in the mode list
+    // applied whenever a word list is chosen as the test
+    pub mode: TestVariant,
 
     pub length_list: StatefulList<String>,
     pub frequency_list: StatefulList<String>,
     pub tests_list: StatefulList<String>,
     pub mods_list: StatefulList<String>,
+    pub mode_list: StatefulList<String>,
+    pub rules_list: StatefulList<String>,
     // HM<test.name (file_word_amount, HM<TestIdentity, historic_max_wpm>)>
     // NaN = historic_max_wpm wasnt cached
     pub info_cache: InfoCache,
     pub script_cache: ScriptCache,
+    pub text_cache: TextCache,
 
     pub database: RunHistoryDatbase,
     pub postbox: PostBox,
+    // the test a practice run took the place of
+    pub before_practice: Option<PracticedTest>,
+}
+
+/// name of the tests made of the weak words of a run
+pub const PRACTICE: &str = "practice";
+
+/// the parts of the test config a practice run swaps out
+pub struct PracticedTest {
+    name: String,
+    variant: TestVariant,
+    text: Option<CustomText>,
 }
 
 impl Default for Settings {
     fn default() -> Self {
-        let length_list = StatefulList::with_items(vec_of_strings!["10", "15", "25", "50", "100"]);
         let words_list = storage::parse_storage_contents();
         let mod_list: Vec<String> = TEST_MODS.left_values().map(|&x| x.to_string()).collect();
-        let test_cfg = TypingTestConfig::default();
+        let mut test_cfg = TypingTestConfig::default();
+        let length_list = create_length_list(test_cfg.variant);
         let mut info_cache: InfoCache = HashMap::new();
         let word_count = count_lines_from_path(test_cfg.get_words_file_path()).unwrap();
 
@@ -294,6 +923,7 @@ impl Default for Settings {
         let conn = Connection::open(&*storage::DATABASE).unwrap();
         database::init::enable_foreign_keys(&conn);
         let max_wpm = database::get_max_wpm(&conn, &test_cfg);
+        test_cfg.confidence = database::confidence::get_confidence(&conn).unwrap_or_default();
 
         let mut hs: HashMap<TestIdentity, Option<f64>> = HashMap::new();
         hs.insert(test_cfg.gib_identity(), max_wpm);
@@ -308,13 +938,18 @@ impl Default for Settings {
             length_list,
             frequency_list,
             info_cache,
+            mode: test_cfg.variant,
+            rules_list: create_rules_list(&test_cfg),
             test_cfg,
             tests_list: StatefulList::with_items(words_list),
             mods_list: StatefulList::with_items(mod_list),
+            mode_list: create_mode_list(),
             colors: SettingsColors::default(),
             script_cache: ScriptCache::default(),
+            text_cache: TextCache::default(),
             database: RunHistoryDatbase::default(),
             postbox: PostBox::default(),
+            before_practice: None,
         }
     }
 }
@@ -324,18 +959,24 @@ impl Settings {
     /// restructure ?? idk
     /// I can't do ..Self::default() as that would count lines twice
     pub fn with_config(colors: SettingsColors, ttc: TypingTestConfig) -> Self {
-        let length_list = StatefulList::with_items(vec_of_strings!["10", "15", "25", "50", "100"]);
         let words_list = storage::parse_storage_contents();
         let mod_list: Vec<String> = TEST_MODS.left_values().map(|&x| x.to_string()).collect();
 
         let mut test_cfg = ttc;
         let word_count = test_cfg.validate();
+        let length_list = create_length_list(test_cfg.variant);
+        let mode = if test_cfg.variant.uses_word_list() {
+            test_cfg.variant
+        } else {
+            TestVariant::Standard
+        };
 
         let mut info_cache: InfoCache = HashMap::new();
 
         let conn = Connection::open(&*storage::DATABASE).unwrap();
         database::init::enable_foreign_keys(&conn);
         let max_wpm = database::get_max_wpm(&conn, &test_cfg);
+        test_cfg.confidence = database::confidence::get_confidence(&conn).unwrap_or_default();
 
         let mut hs: HashMap<TestIdentity, Option<f64>> = HashMap::new();
         hs.insert(test_cfg.gib_identity(), max_wpm);
@@ -350,22 +991,29 @@ impl Settings {
             length_list,
             frequency_list,
             info_cache,
+            mode,
+            rules_list: create_rules_list(&test_cfg),
             test_cfg,
             tests_list: StatefulList::with_items(words_list),
             mods_list: StatefulList::with_items(mod_list),
+            mode_list: create_mode_list(),
             script_cache: ScriptCache::default(),
+            text_cache: TextCache::default(),
             database: RunHistoryDatbase::default(),
             postbox: PostBox::default(),
+            before_practice: None,
             colors,
         }
     }
 
     pub fn color_hover_or_active(&self) -> HashMap<SetList, Option<Color>> {
-        let mut hm: HashMap<SetList, Option<Color>> = HashMap::with_capacity(4);
+        let mut hm: HashMap<SetList, Option<Color>> = HashMap::with_capacity(6);
         hm.insert(SetList::Length, None);
         hm.insert(SetList::Test, None);
         hm.insert(SetList::Frequency, None);
         hm.insert(SetList::Mods, None);
+        hm.insert(SetList::Mode, None);
+        hm.insert(SetList::Rules, None);
 
         if self.hovered != SetList::Nil {
             hm.insert(self.hovered, Some(self.colors.hover));
@@ -388,7 +1036,7 @@ impl Settings {
 
     pub fn update_historic_max_wpm(&mut self, max_wpm: f64) {
         match self.test_cfg.variant {
-            TestVariant::Standard => {
+            TestVariant::Standard | TestVariant::Timed | TestVariant::Drill => {
                 *self
                     .info_cache
                     .get_mut(&self.test_cfg.name)
@@ -400,6 +1048,12 @@ impl Settings {
             TestVariant::Script => {
                 *self.script_cache.get_mut(&self.test_cfg.name).unwrap() = Some(max_wpm);
             }
+            TestVariant::Quote | TestVariant::Code | TestVariant::Text | TestVariant::Lesson => {
+                self.text_cache.insert(
+                    (self.test_cfg.name.clone(), self.test_cfg.gib_identity()),
+                    Some(max_wpm),
+                );
+            }
         }
     }
 
@@ -417,15 +1071,21 @@ impl Settings {
 
     /// This function performs actions needed after test termination
     /// that includes saving results to db and caching new max_wpm if need be
-    pub fn save_test_results(&mut self, summary: TestSummary) {
+    /// returns the id of the saved run
+    pub fn save_test_results(&mut self, summary: TestSummary) -> i64 {
         self.test_cfg.test_summary = summary;
         let final_wpm = self.test_cfg.test_summary.wpm;
 
+        // failed runs are kept only for the history
+        if self.test_cfg.test_summary.failed {
+            return self.database.save(&self.test_cfg);
+        }
+
         // If record is beat the historic_max_wpm but the
         // previous one is cached so it can be displayed in
         // the post screen
         match self.test_cfg.variant {
-            TestVariant::Standard => {
+            TestVariant::Standard | TestVariant::Timed | TestVariant::Drill => {
                 let historic_max_wpm: f64 = self.get_current_historic_max_wpm().unwrap_or(0.);
 
                 self.postbox.cached_historic_wpm = historic_max_wpm;
@@ -433,7 +1093,6 @@ impl Settings {
                 if final_wpm > historic_max_wpm {
                     self.update_historic_max_wpm(final_wpm);
                 }
-                self.database.save(&self.test_cfg);
             }
 
             TestVariant::Script => {
@@ -448,11 +1107,153 @@ impl Settings {
                 if final_wpm > historic_max_wpm {
                     *self.script_cache.get_mut(&self.test_cfg.name).unwrap() = Some(final_wpm);
                 }
-                self.database.save(&self.test_cfg);
             }
+
+            TestVariant::Quote | TestVariant::Code | TestVariant::Text | TestVariant::Lesson => {
+                let conn = &self.database.conn;
+                let test_cfg = &self.test_cfg;
+                let historic_max_wpm = self
+                    .text_cache
+                    .entry((test_cfg.name.clone(), test_cfg.gib_identity()))
+                    .or_insert_with(|| database::get_max_wpm(conn, test_cfg))
+                    .unwrap_or(0.);
+
+                self.postbox.cached_historic_wpm = historic_max_wpm;
+                if final_wpm > historic_max_wpm {
+                    self.update_historic_max_wpm(final_wpm);
+                }
+            }
+        }
+        self.database.save(&self.test_cfg)
+    }
+
+    /// gathers the key and word stats shown on the post screen
+    /// and lets the run update the confidence in its letters
+    /// the run has to be saved beforehand to count towards the recent ones
+    pub fn cache_run_stats(&mut self, test: &TestState) {
+        self.postbox.key_stats = KeyStats::from_keystrokes(&test.keystrokes);
+        self.test_cfg.confidence.update(&self.postbox.key_stats);
+        database::confidence::save_confidence(&self.database.conn, &self.test_cfg.confidence)
+            .expect("saving the letter confidence");
+        self.postbox.words = WordReport::from_words(&test.words, REPORTED_WORDS);
+        self.postbox.timeline =
+            replay::timeline(test.pristine.clone(), &test.keystrokes, &self.test_cfg);
+
+        let mut recent = KeyStats::default();
+        for log in database::keystrokes::get_recent_keystrokes(&self.database.conn, HEATMAP_RUNS)
+            .expect("loading recent keystrokes")
+        {
+            recent.add_run(&log);
+        }
+        self.postbox.recent_key_stats = recent;
+    }
+
+    /// picks up the lesson of the word list where it was left off
+    /// has to be called before a lesson test is prepared
+    pub fn load_lesson(&mut self) {
+        if self.test_cfg.variant != TestVariant::Lesson {
+            return;
+        }
+        let name = &self.test_cfg.name;
+        if matches!(&self.test_cfg.lesson, Some(lesson) if &lesson.name == name) {
+            return;
+        }
+
+        let path = self.test_cfg.get_words_file_path();
+        let mut lesson = match Lesson::from_word_list(name, &path) {
+            Ok(lesson) => lesson,
+            Err(err) => {
+                termprep::panic_with_friendly_message(&format!(
+                    "{:?}\ncouldn't read this word list: {}",
+                    path, err
+                ));
+                return;
+            }
+        };
+        if let Some((unlocked, streak)) =
+            database::lesson::get_lesson_progress(&self.database.conn, name)
+        {
+            lesson.unlocked = unlocked.clamp(lesson.unlocked, lesson.letters.len());
+            lesson.streak = streak;
+        }
+        self.test_cfg.lesson = Some(lesson);
+    }
+
+    /// picks the n-grams of the upcoming drill
+    /// the ones given in the config or the weakest of the recent runs
+    pub fn load_drill(&mut self) {
+        if self.test_cfg.variant != TestVariant::Drill {
+            return;
+        }
+        if !self.test_cfg.ngrams.is_empty() {
+            self.test_cfg.drill = self.test_cfg.ngrams.clone();
+            return;
+        }
+
+        let mut stats = NgramStats::default();
+        let conn = &self.database.conn;
+        for log in database::keystrokes::get_recent_keystrokes(conn, DRILL_RUNS).unwrap_or_default()
+        {
+            stats.add_run(&log);
+        }
+        self.test_cfg.drill = stats.weakest(DRILLED_NGRAMS);
+    }
+
+    /// counts the finished lesson run towards unlocking the next letter
+    /// failed runs break the streak
+    pub fn record_lesson_run(&mut self) {
+        self.postbox.unlocked = None;
+        if self.test_cfg.variant != TestVariant::Lesson {
+            return;
+        }
+        if let Some(lesson) = &mut self.test_cfg.lesson {
+            let summary = &self.test_cfg.test_summary;
+            self.postbox.unlocked = match summary.failed {
+                true => {
+                    lesson.streak = 0;
+                    None
+                }
+                false => lesson.record_run(summary.wpm, summary.acc),
+            };
+            database::lesson::save_lesson_progress(&self.database.conn, lesson)
+                .expect("saving the lesson progress");
         }
     }
 
+    /// wpm the pace caret should move at in the upcoming test
+    /// None if there is no pace or no runs to take it from
+    pub fn pace_wpm(&self) -> Option<f64> {
+        let conn = &self.database.conn;
+        match self.test_cfg.pace {
+            Pace::Off => None,
+            Pace::Wpm(wpm) => Some(wpm as f64),
+            Pace::Average => database::get_average_wpm(conn, &self.test_cfg, PACE_AVERAGE_RUNS),
+            Pace::Best => match self.test_cfg.variant {
+                TestVariant::Script => database::get_max_wpm_script(conn, &self.test_cfg),
+                _ => database::get_max_wpm(conn, &self.test_cfg),
+            },
+        }
+    }
+
+    /// timeline of the best run of the upcoming test
+    pub fn ghost(&self) -> Timeline {
+        if !self.test_cfg.ghost {
+            return vec![];
+        }
+        let conn = &self.database.conn;
+        database::ghost::get_ghost_run(conn, &self.test_cfg)
+            .and_then(|run_id| database::keystrokes::get_recording(conn, run_id))
+            .map_or(vec![], |recording| {
+                let config = TypingTestConfig {
+                    skip_indent: recording.skip_indent,
+                    error_policy: recording.error_policy,
+                    ..TypingTestConfig::default()
+                };
+                let lines = langs::text_to_lines(&recording.text, &TestColors::default());
+                replay::timeline(lines, &recording.keystrokes, &config)
+            })
+    }
+
     pub fn save_run_to_database(&mut self) {
         self.database.save(&self.test_cfg);
     }
@@ -472,6 +1273,11 @@ impl Settings {
         }
     }
 
+    fn cache_historic_max_wpm_script(&mut self) {
+        let hwpm = database::get_max_wpm_script(&self.database.conn, &self.test_cfg);
+        self.script_cache.insert(self.test_cfg.name.clone(), hwpm);
+    }
+
     /// TODO
     /// this function is really bad
     fn get_word_count(&mut self) -> usize {
@@ -500,27 +1306,48 @@ impl Settings {
         }
 
         match self.active {
-            SetList::Length => {
-                if let TestVariant::Script = self.test_cfg.variant {
-                    return;
+            SetList::Length => match self.test_cfg.variant {
+                TestVariant::Standard | TestVariant::Drill => {
+                    self.test_cfg.length = self.length_list.get_item().parse::<usize>().unwrap();
+                    self.cache_historic_max_wpm();
                 }
-                self.test_cfg.length = self.length_list.get_item().parse::<usize>().unwrap();
-                self.cache_historic_max_wpm();
-            }
+                TestVariant::Lesson => {
+                    self.test_cfg.length = self.length_list.get_item().parse::<usize>().unwrap();
+                }
+                TestVariant::Timed => {
+                    self.test_cfg.duration = self
+                        .length_list
+                        .get_item()
+                        .trim_end_matches('s')
+                        .parse::<u64>()
+                        .unwrap();
+                    self.cache_historic_max_wpm();
+                }
+                TestVariant::Quote => {
+                    self.test_cfg.quote_length = *QUOTE_LENGTHS
+                        .get_by_left(self.length_list.get_item() as &str)
+                        .expect("UI doesn't match QUOTE_LENGTHS");
+                }
+                TestVariant::Script | TestVariant::Code | TestVariant::Text => {}
+            },
 
             SetList::Test => {
-                let chosen_test_name = self.tests_list.get_item();
+                let chosen_test_name = self.tests_list.get_item().clone();
 
-                if is_script(chosen_test_name) {
-                    self.test_cfg.variant = TestVariant::Script;
+                if is_script(&chosen_test_name) {
+                    self.set_variant(TestVariant::Script);
 
                     // TODO change
                     self.test_cfg.name = chosen_test_name[2..].to_string();
-                    let hwpm =
-                        database::get_max_wpm_script(&self.database.conn, &self.test_cfg.name);
-                    self.script_cache.insert(self.test_cfg.name.clone(), hwpm);
+                    self.cache_historic_max_wpm_script();
+                } else if is_quote(&chosen_test_name) {
+                    self.set_variant(TestVariant::Quote);
+                    self.test_cfg.name = chosen_test_name[QUOTE_SIGN.len()..].to_string();
+                } else if is_code(&chosen_test_name) {
+                    self.set_variant(TestVariant::Code);
+                    self.test_cfg.name = chosen_test_name[CODE_SIGN.len()..].to_string();
                 } else {
-                    self.test_cfg.variant = TestVariant::Standard;
+                    self.set_variant(self.mode);
                     self.test_cfg.name = chosen_test_name.to_string();
 
                     let word_count = self.get_word_count();
@@ -535,7 +1362,7 @@ impl Settings {
             }
 
             SetList::Frequency => {
-                if let TestVariant::Script = self.test_cfg.variant {
+                if !self.test_cfg.variant.uses_word_list() {
                     return;
                 }
                 self.test_cfg.word_pool = self
@@ -547,7 +1374,7 @@ impl Settings {
             }
 
             SetList::Mods => {
-                if let TestVariant::Script = self.test_cfg.variant {
+                if !self.test_cfg.variant.uses_word_list() {
                     return;
                 }
                 let test_mod = TEST_MODS
@@ -559,16 +1386,118 @@ impl Settings {
                 }
                 self.cache_historic_max_wpm();
             }
+
+            SetList::Mode => {
+                self.mode = *TEST_MODES
+                    .get_by_left(self.mode_list.get_item() as &str)
+                    .expect("UI doesn't match TEST_MODES");
+
+                if !self.test_cfg.variant.uses_word_list() {
+                    return;
+                }
+                self.set_variant(self.mode);
+                self.cache_historic_max_wpm();
+            }
+
+            SetList::Rules => {
+                let selected = self.rules_list.state.selected().unwrap_or(0);
+                // returns whether records are kept separately for the rule
+                let splits_records = match RULES[selected] {
+                    TestRule::Errors => {
+                        self.test_cfg.error_policy = self.test_cfg.error_policy.next();
+                        true
+                    }
+                    TestRule::Fail => {
+                        self.test_cfg.fail_policy = self.test_cfg.fail_policy.next();
+                        false
+                    }
+                    TestRule::Backspace => {
+                        self.test_cfg.backspace = self.test_cfg.backspace.next();
+                        true
+                    }
+                    TestRule::Pace => {
+                        self.test_cfg.pace = self.test_cfg.pace.next();
+                        false
+                    }
+                    TestRule::Ghost => {
+                        self.test_cfg.ghost = !self.test_cfg.ghost;
+                        false
+                    }
+                };
+                self.rules_list.items = create_rules_list(&self.test_cfg).items;
+
+                if !splits_records {
+                    return;
+                }
+                match self.test_cfg.variant {
+                    TestVariant::Standard | TestVariant::Timed | TestVariant::Drill => {
+                        self.cache_historic_max_wpm()
+                    }
+                    TestVariant::Script => self.cache_historic_max_wpm_script(),
+                    TestVariant::Quote
+                    | TestVariant::Code
+                    | TestVariant::Text
+                    | TestVariant::Lesson => {}
+                }
+            }
             SetList::Nil => unreachable!(),
         }
     }
 
+    /// makes the text given through the command line the current test
+    pub fn set_custom_text(&mut self, name: &str, text: &str) -> anyhow::Result<()> {
+        let text =
+            CustomText::new(text).ok_or_else(|| anyhow::anyhow!("there is nothing to type"))?;
+        self.set_variant(TestVariant::Text);
+        self.test_cfg.name = name.to_string();
+        self.test_cfg.text = Some(text);
+        Ok(())
+    }
+
+    /// types the given text once, the test it replaces is put aside until end_practice
+    pub fn start_practice(&mut self, text: &str) -> anyhow::Result<()> {
+        let before = PracticedTest {
+            name: self.test_cfg.name.clone(),
+            variant: self.test_cfg.variant,
+            text: self.test_cfg.text.clone(),
+        };
+        self.set_custom_text(PRACTICE, text)?;
+        // practicing again keeps the test from before the first practice
+        self.before_practice.get_or_insert(before);
+        Ok(())
+    }
+
+    /// brings back the test the practice took the place of
+    /// returns false if there was no practice going on
+    pub fn end_practice(&mut self) -> bool {
+        match self.before_practice.take() {
+            Some(before) => {
+                self.set_variant(before.variant);
+                self.test_cfg.name = before.name;
+                self.test_cfg.text = before.text;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// changes the test variant and swaps out the length list
+    /// as for example timed tests measure their length in seconds
+    fn set_variant(&mut self, variant: TestVariant) {
+        if self.test_cfg.variant != variant {
+            self.test_cfg.variant = variant;
+            self.length_list = create_length_list(variant);
+        }
+    }
+
     pub fn up(&mut self) {
         match self.hovered {
             SetList::Length => self.hovered = SetList::Test,
             SetList::Test => self.hovered = SetList::Length,
             SetList::Frequency => self.hovered = SetList::Mods,
             SetList::Mods => self.hovered = SetList::Frequency,
+            SetList::Mode => self.hovered = SetList::Rules,
+            SetList::Rules => self.hovered = SetList::Mode,
             SetList::Nil => {
                 self.get_list(self.active).unwrap().previous();
             }
@@ -581,6 +1510,8 @@ impl Settings {
             SetList::Test => self.hovered = SetList::Length,
             SetList::Frequency => self.hovered = SetList::Mods,
             SetList::Mods => self.hovered = SetList::Frequency,
+            SetList::Mode => self.hovered = SetList::Rules,
+            SetList::Rules => self.hovered = SetList::Mode,
             SetList::Nil => {
                 self.get_list(self.active).unwrap().next();
             }
@@ -589,10 +1520,12 @@ impl Settings {
 
     pub fn left(&mut self) {
         match self.hovered {
-            SetList::Length => self.hovered = SetList::Frequency,
-            SetList::Test => self.hovered = SetList::Mods,
+            SetList::Length => self.hovered = SetList::Mode,
+            SetList::Test => self.hovered = SetList::Rules,
             SetList::Frequency => self.hovered = SetList::Length,
             SetList::Mods => self.hovered = SetList::Test,
+            SetList::Mode => self.hovered = SetList::Frequency,
+            SetList::Rules => self.hovered = SetList::Mods,
             SetList::Nil => {
                 self.hovered = self.active;
                 self.active = SetList::Nil;
@@ -605,8 +1538,10 @@ impl Settings {
         match self.hovered {
             SetList::Length => self.hovered = SetList::Frequency,
             SetList::Test => self.hovered = SetList::Mods,
-            SetList::Frequency => self.hovered = SetList::Length,
-            SetList::Mods => self.hovered = SetList::Test,
+            SetList::Frequency => self.hovered = SetList::Mode,
+            SetList::Mods => self.hovered = SetList::Rules,
+            SetList::Mode => self.hovered = SetList::Length,
+            SetList::Rules => self.hovered = SetList::Test,
             SetList::Nil => {
                 self.hovered = self.active;
                 self.active = SetList::Nil;
@@ -621,11 +1556,46 @@ impl Settings {
             SetList::Frequency => Some(&mut self.frequency_list),
             SetList::Mods => Some(&mut self.mods_list),
             SetList::Test => Some(&mut self.tests_list),
+            SetList::Mode => Some(&mut self.mode_list),
+            SetList::Rules => Some(&mut self.rules_list),
             SetList::Nil => None,
         }
     }
 }
 
+fn create_length_list(variant: TestVariant) -> StatefulList<String> {
+    match variant {
+        TestVariant::Timed => {
+            StatefulList::with_items(vec_of_strings!["15s", "30s", "60s", "120s"])
+        }
+        TestVariant::Quote => {
+            StatefulList::with_items(vec_of_strings!["all", "short", "medium", "long", "thicc"])
+        }
+        _ => StatefulList::with_items(vec_of_strings!["10", "15", "25", "50", "100"]),
+    }
+}
+
+fn create_mode_list() -> StatefulList<String> {
+    StatefulList::with_items(vec_of_strings!["words", "time", "lesson", "drill"])
+}
+
+fn create_rules_list(test_cfg: &TypingTestConfig) -> StatefulList<String> {
+    let items = RULES
+        .iter()
+        .map(|rule| match rule {
+            TestRule::Errors => format!("errors: {}", test_cfg.error_policy),
+            TestRule::Fail => match test_cfg.fail_policy {
+                FailPolicy::MinAccuracy => format!("fail: below {}% acc", test_cfg.min_acc),
+                policy => format!("fail: {}", policy),
+            },
+            TestRule::Backspace => format!("backspace: {}", test_cfg.backspace),
+            TestRule::Pace => format!("pace: {}", test_cfg.pace),
+            TestRule::Ghost => format!("ghost: {}", if test_cfg.ghost { "pb" } else { "off" }),
+        })
+        .collect();
+    StatefulList::with_items(items)
+}
+
 fn create_frequency_list(word_count: usize) -> StatefulList<String> {
     let mut initial: Vec<String> = [100, 1000, 5000, 10000, 20000, 50000]
         .iter()
@@ -669,4 +1639,19 @@ mod tests {
         let zero_ans = decode_test_mod_bitflags(0);
         assert!(zero_ans.is_empty());
     }
+
+    #[test]
+    fn test_drill_identity() {
+        let mut ttc = TypingTestConfig {
+            variant: TestVariant::Drill,
+            drill: vec_of_strings!["th", "ing"],
+            ..TypingTestConfig::default()
+        };
+        let drill = ttc.gib_identity();
+
+        ttc.drill = vec_of_strings!["ing", "th"];
+        assert_eq!(ttc.gib_identity(), drill);
+        ttc.drill = vec_of_strings!["th", "ion"];
+        assert_ne!(ttc.gib_identity(), drill);
+    }
 }
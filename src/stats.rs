@@ -0,0 +1,541 @@
+//! statistics gathered from keystroke logs
+
+use crate::typer::{Keystroke, Timeline, WordStat};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// every weak word shows up this many times in a practice test
+const PRACTICE_REPEATS: usize = 3;
+/// how far a single press moves the confidence in its letter
+const CONFIDENCE_STEP: f64 = 0.1;
+/// bigrams and trigrams
+const NGRAM_SIZES: [usize; 2] = [2, 3];
+/// n-grams typed fewer times than this aren't ranked
+const MIN_NGRAM_COUNT: u32 = 3;
+
+/// rolling wpm is taken over this much of the run
+pub const ROLLING_WINDOW: Duration = Duration::from_secs(2);
+
+/// the key that finished a word counts towards it as well
+fn word_wpm(chars: usize, ms: u32) -> f64 {
+    12. * chars as f64 / (ms.max(1) as f64 / 1000.)
+}
+
+/// wpm over the window before each of the points in time given in seconds
+/// comes from the timeline, so mistakes count as typed chars
+pub fn rolling_wpm(timeline: &Timeline, at: &[f64], window: Duration) -> Vec<(f64, f64)> {
+    let chars_at = |ms: f64| {
+        let passed = timeline.partition_point(|&(t, _)| t as f64 <= ms);
+        passed.checked_sub(1).map_or(0, |i| timeline[i].1) as f64
+    };
+
+    at.iter()
+        .map(|&sec| {
+            let ms = sec * 1000.;
+            let span = ms.min(window.as_millis() as f64);
+            let typed = chars_at(ms) - chars_at(ms - span);
+            (sec, (12. * typed / (span / 1000.)).max(0.))
+        })
+        .collect()
+}
+
+/// wpm of every word, placed where the word was done
+pub fn burst_wpm(words: &[WordStat]) -> Vec<(f64, f64)> {
+    words
+        .iter()
+        .map(|stat| {
+            let wpm = word_wpm(stat.word.chars().count() + 1, stat.ms);
+            (stat.end as f64 / 1000., wpm)
+        })
+        .collect()
+}
+
+/// seconds into the run at which wrong keys were pressed
+pub fn mistake_times(keystrokes: &[Keystroke]) -> Vec<f64> {
+    keystrokes
+        .iter()
+        .filter(|key| !key.correction && key.typed != key.expected)
+        .map(|key| key.ms as f64 / 1000.)
+        .collect()
+}
+
+/// mistakes per second in buckets as long as the wpm hoarder's
+/// the last bucket takes in the unfinished second the run ended on
+pub fn mistakes_per_second(mistakes: &[f64], seconds: u64, buckets: usize) -> Vec<(f64, f64)> {
+    let secs = seconds as f64;
+    let mut counts = vec![0; buckets];
+    for &at in mistakes {
+        if let Some(count) = counts.get_mut(((at / secs) as usize).min(buckets.saturating_sub(1))) {
+            *count += 1;
+        }
+    }
+    counts
+        .into_iter()
+        .enumerate()
+        .map(|(i, count)| ((i + 1) as f64 * secs, count as f64 / secs))
+        .collect()
+}
+
+/// how a single key fared
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct KeyStat {
+    pub presses: u32,
+    pub misses: u32,
+    /// sum of the time it took to get to the key after the previous one
+    pub latency_ms: u64,
+    /// presses that had a previous keystroke to measure the latency from
+    pub timed: u32,
+}
+
+impl KeyStat {
+    pub fn error_rate(&self) -> f64 {
+        self.misses as f64 / self.presses as f64
+    }
+
+    pub fn mean_latency(&self) -> Option<f64> {
+        (self.timed > 0).then(|| self.latency_ms as f64 / self.timed as f64)
+    }
+}
+
+/// stats of every key that was supposed to be pressed
+/// uppercase letters count towards their lowercase key
+#[derive(Debug, Default)]
+pub struct KeyStats {
+    keys: HashMap<char, KeyStat>,
+}
+
+impl KeyStats {
+    pub fn from_keystrokes(keystrokes: &[Keystroke]) -> Self {
+        let mut stats = Self::default();
+        stats.add_run(keystrokes);
+        stats
+    }
+
+    /// corrections aren't counted, they still delay the next key though
+    pub fn add_run(&mut self, keystrokes: &[Keystroke]) {
+        let mut previous: Option<u32> = None;
+        for key in keystrokes {
+            if !key.correction {
+                let expected = key.expected.to_lowercase().next().unwrap_or(key.expected);
+                let stat = self.keys.entry(expected).or_default();
+                stat.presses += 1;
+                if key.typed != key.expected {
+                    stat.misses += 1;
+                }
+                if let Some(previous) = previous {
+                    stat.latency_ms += key.ms.saturating_sub(previous) as u64;
+                    stat.timed += 1;
+                }
+            }
+            previous = Some(key.ms);
+        }
+    }
+
+    pub fn get(&self, key: char) -> Option<&KeyStat> {
+        self.keys.get(&key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&char, &KeyStat)> {
+        self.keys.iter()
+    }
+
+    /// latency of all keys taken together
+    pub fn mean_latency(&self) -> Option<f64> {
+        let (sum, timed) = self.keys.values().fold((0, 0), |(sum, timed), stat| {
+            (sum + stat.latency_ms, timed + stat.timed)
+        });
+        (timed > 0).then(|| sum as f64 / timed as f64)
+    }
+
+    /// key with the highest error rate, None if nothing was missed
+    pub fn most_missed(&self) -> Option<(char, f64)> {
+        self.keys
+            .iter()
+            .filter(|(_, stat)| stat.misses > 0)
+            .map(|(&key, stat)| (key, stat.error_rate()))
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+    }
+
+    /// key that took the longest to get to on average
+    pub fn slowest(&self) -> Option<(char, f64)> {
+        self.keys
+            .iter()
+            .filter(|(key, _)| !key.is_whitespace())
+            .filter_map(|(&key, stat)| Some((key, stat.mean_latency()?)))
+            .max_by(|a, b| a.1.total_cmp(&b.1).then(b.0.cmp(&a.0)))
+    }
+}
+
+/// how sure the typist is of each letter, from 0 to 1
+/// every run nudges the letters typed in it
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct LetterConfidence {
+    letters: HashMap<char, f64>,
+}
+
+impl LetterConfidence {
+    pub fn get(&self, letter: char) -> Option<f64> {
+        self.letters.get(&letter).copied()
+    }
+
+    pub fn set(&mut self, letter: char, confidence: f64) {
+        self.letters.insert(letter, confidence.clamp(0., 1.));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (char, f64)> + '_ {
+        self.letters
+            .iter()
+            .map(|(&letter, &confidence)| (letter, confidence))
+    }
+
+    /// a letter scores its accuracy, slowed down by how much longer
+    /// it took to reach than the average key of the run
+    /// the more presses the closer the confidence gets to the score
+    pub fn update(&mut self, run: &KeyStats) {
+        let mean_latency = run.mean_latency();
+        for (&letter, stat) in run.iter().filter(|(key, _)| key.is_alphabetic()) {
+            let speed = match (mean_latency, stat.mean_latency()) {
+                (Some(mean), Some(latency)) if latency > 0. => (mean / latency).min(1.),
+                _ => 1.,
+            };
+            let score = (1. - stat.error_rate()) * speed;
+
+            let confidence = match self.get(letter) {
+                Some(confidence) => {
+                    let step = 1. - (1. - CONFIDENCE_STEP).powi(stat.presses as i32);
+                    confidence + (score - confidence) * step
+                }
+                None => score,
+            };
+            self.set(letter, confidence);
+        }
+    }
+
+    /// up to n letters with the lowest confidence, weakest first
+    pub fn weakest(&self, n: usize) -> Vec<char> {
+        let mut letters: Vec<(char, f64)> = self.iter().collect();
+        letters.sort_by(|a, b| a.1.total_cmp(&b.1).then(a.0.cmp(&b.0)));
+        letters
+            .into_iter()
+            .take(n)
+            .map(|(letter, _)| letter)
+            .collect()
+    }
+}
+
+/// how a sequence of letters fared
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct NgramStat {
+    pub count: u32,
+    /// times any of its letters was missed
+    pub misses: u32,
+    /// sum of the time from the key before it to its last letter
+    pub ms: u64,
+}
+
+impl NgramStat {
+    /// average time per key, mistakes make it count for more
+    fn cost(&self, len: usize) -> f64 {
+        let per_key = self.ms as f64 / (self.count as f64 * len as f64);
+        per_key * (1. + self.misses as f64 / self.count as f64)
+    }
+}
+
+/// stats of the bigrams and trigrams of words
+#[derive(Debug, Default)]
+pub struct NgramStats {
+    ngrams: HashMap<String, NgramStat>,
+}
+
+impl NgramStats {
+    /// only letters typed in a row count, corrections break the row
+    /// the key before the n-gram is needed to time its first letter
+    pub fn add_run(&mut self, keystrokes: &[Keystroke]) {
+        let mut row: Vec<Keystroke> = vec![];
+        for &key in keystrokes {
+            if key.correction {
+                row.clear();
+                continue;
+            }
+            if !key.expected.is_alphabetic() {
+                row.clear();
+                row.push(key);
+                continue;
+            }
+            row.push(key);
+
+            for n in NGRAM_SIZES {
+                if row.len() <= n {
+                    continue;
+                }
+                let window = &row[row.len() - n - 1..];
+                let ngram: String = window[1..]
+                    .iter()
+                    .flat_map(|key| key.expected.to_lowercase())
+                    .collect();
+
+                let stat = self.ngrams.entry(ngram).or_default();
+                stat.count += 1;
+                stat.ms += key.ms.saturating_sub(window[0].ms) as u64;
+                if window[1..].iter().any(|key| key.typed != key.expected) {
+                    stat.misses += 1;
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, ngram: &str) -> Option<&NgramStat> {
+        self.ngrams.get(ngram)
+    }
+
+    /// up to n n-grams that are the slowest and most missed, worst first
+    pub fn weakest(&self, n: usize) -> Vec<String> {
+        let mut ranked: Vec<(&String, f64)> = self
+            .ngrams
+            .iter()
+            .filter(|(_, stat)| stat.count >= MIN_NGRAM_COUNT)
+            .map(|(ngram, stat)| (ngram, stat.cost(ngram.chars().count())))
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(b.0)));
+        ranked
+            .into_iter()
+            .take(n)
+            .map(|(ngram, _)| ngram.clone())
+            .collect()
+    }
+}
+
+/// the words that gave the most trouble in a test
+#[derive(Debug, Default, PartialEq)]
+pub struct WordReport {
+    /// words with the lowest wpm
+    pub slowest: Vec<(String, f64)>,
+    /// words with the most wrong keys pressed
+    pub most_missed: Vec<(String, usize)>,
+}
+
+impl WordReport {
+    /// ranks up to n words in each category, repeated words are added up
+    pub fn from_words(words: &[WordStat], n: usize) -> Self {
+        // word -> (ms, misses, times typed)
+        let mut totals: HashMap<&str, (u32, usize, u32)> = HashMap::new();
+        for stat in words {
+            let total = totals.entry(&stat.word).or_default();
+            total.0 += stat.ms;
+            total.1 += stat.misses;
+            total.2 += 1;
+        }
+
+        let wpm = |word: &str, ms: u32, times: u32| {
+            word_wpm((word.chars().count() + 1) * times as usize, ms)
+        };
+
+        let mut slowest: Vec<(String, f64)> = totals
+            .iter()
+            .map(|(word, &(ms, _, times))| (word.to_string(), wpm(word, ms, times)))
+            .collect();
+        slowest.sort_by(|a, b| a.1.total_cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+        slowest.truncate(n);
+
+        let mut most_missed: Vec<(String, usize)> = totals
+            .iter()
+            .filter(|(_, &(_, misses, _))| misses > 0)
+            .map(|(word, &(_, misses, _))| (word.to_string(), misses))
+            .collect();
+        most_missed.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        most_missed.truncate(n);
+
+        Self {
+            slowest,
+            most_missed,
+        }
+    }
+
+    /// text made of the reported words, each repeated a few times
+    /// None if there is nothing to practice
+    pub fn practice_text(&self) -> Option<String> {
+        let mut words: Vec<&str> = vec![];
+        let reported = self.slowest.iter().map(|(word, _)| word);
+        for word in reported.chain(self.most_missed.iter().map(|(word, _)| word)) {
+            if !words.contains(&word.as_str()) {
+                words.push(word);
+            }
+        }
+
+        if words.is_empty() {
+            return None;
+        }
+
+        let mut practice: Vec<&str> = words
+            .iter()
+            .flat_map(|word| [*word; PRACTICE_REPEATS])
+            .collect();
+        fastrand::shuffle(&mut practice);
+        Some(practice.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::typer::BACKSPACE;
+
+    fn key(ms: u32, expected: char, typed: char) -> Keystroke {
+        Keystroke {
+            ms,
+            expected,
+            typed,
+            correction: typed == BACKSPACE,
+        }
+    }
+
+    fn word(word: &str, ms: u32, misses: usize) -> WordStat {
+        WordStat {
+            word: word.to_string(),
+            ms,
+            misses,
+            at: 0,
+            end: 0,
+        }
+    }
+
+    #[test]
+    fn test_wpm_series() {
+        // 5 chars a second for 2 seconds, then a second long pause
+        let timeline: Timeline = (1..=10).map(|i| (i * 200, i)).collect();
+        let rolling = rolling_wpm(&timeline, &[1., 2., 3., 4.], ROLLING_WINDOW);
+        assert_eq!(rolling, vec![(1., 60.), (2., 60.), (3., 30.), (4., 0.)]);
+
+        let words = vec![WordStat {
+            end: 1500,
+            ..word("four", 1000, 0)
+        }];
+        assert_eq!(burst_wpm(&words), vec![(1.5, 60.)]);
+    }
+
+    #[test]
+    fn test_mistake_series() {
+        let keystrokes = vec![
+            key(500, 'a', 'a'),
+            key(900, 'a', 's'),
+            key(1000, 's', BACKSPACE),
+            key(2500, 'a', 'd'),
+            key(4200, 'a', 'f'),
+        ];
+        let mistakes = mistake_times(&keystrokes);
+        assert_eq!(mistakes, vec![0.9, 2.5, 4.2]);
+        // the hoarder halved once so a bucket spans 2 seconds
+        assert_eq!(
+            mistakes_per_second(&mistakes, 2, 2),
+            vec![(2., 0.5), (4., 1.)]
+        );
+    }
+
+    #[test]
+    fn test_word_report() {
+        let words = vec![
+            word("the", 200, 0),
+            word("rhythm", 2100, 2),
+            word("the", 600, 1),
+            word("fox", 400, 0),
+        ];
+        let report = WordReport::from_words(&words, 2);
+
+        let slowest: Vec<&str> = report.slowest.iter().map(|(w, _)| w.as_str()).collect();
+        assert_eq!(slowest, vec!["rhythm", "fox"]);
+        // 7 chars in 2.1s
+        assert_eq!(report.slowest[0].1, 40.);
+
+        assert_eq!(
+            report.most_missed,
+            vec![("rhythm".to_string(), 2), ("the".to_string(), 1)]
+        );
+
+        let practice = report.practice_text().unwrap();
+        let mut practice: Vec<&str> = practice.split(' ').collect();
+        practice.sort_unstable();
+        assert_eq!(
+            practice,
+            vec!["fox", "fox", "fox", "rhythm", "rhythm", "rhythm", "the", "the", "the"]
+        );
+
+        assert!(WordReport::default().practice_text().is_none());
+    }
+
+    #[test]
+    fn test_key_stats() {
+        let keystrokes = vec![
+            key(0, 'A', 'A'),
+            key(100, 'b', 'v'),
+            key(300, 'c', BACKSPACE),
+            key(400, 'b', 'b'),
+            key(700, 'a', 'a'),
+        ];
+        let stats = KeyStats::from_keystrokes(&keystrokes);
+
+        let a = stats.get('a').unwrap();
+        assert_eq!((a.presses, a.misses, a.timed), (2, 0, 1));
+        assert_eq!(a.mean_latency(), Some(300.));
+
+        let b = stats.get('b').unwrap();
+        assert_eq!((b.presses, b.misses), (2, 1));
+        assert_eq!(b.error_rate(), 0.5);
+        // the backspace in between counts as the previous keystroke
+        assert_eq!(b.mean_latency(), Some(100.));
+
+        assert!(stats.get('c').is_none());
+        assert_eq!(stats.most_missed(), Some(('b', 0.5)));
+        assert_eq!(stats.slowest(), Some(('a', 300.)));
+        assert_eq!(stats.mean_latency(), Some(500. / 3.));
+    }
+
+    #[test]
+    fn test_ngram_stats() {
+        let mut stats = NgramStats::default();
+        for _ in 0..MIN_NGRAM_COUNT {
+            stats.add_run(&[
+                key(0, ' ', ' '),
+                key(100, 't', 't'),
+                key(200, 'h', 'h'),
+                key(600, 'e', 'r'),
+                key(700, 'e', BACKSPACE),
+                key(800, 'e', 'e'),
+                key(900, 'n', 'n'),
+            ]);
+        }
+
+        let th = stats.get("th").unwrap();
+        assert_eq!((th.count, th.misses, th.ms), (3, 0, 600));
+        let he = stats.get("he").unwrap();
+        assert_eq!((he.count, he.misses, he.ms), (3, 3, 1500));
+        assert_eq!(stats.get("the").unwrap().ms, 1800);
+        // the correction starts a new row, e only times n
+        assert!(stats.get("en").is_none());
+
+        assert_eq!(stats.weakest(2), vec!["he", "the"]);
+        assert_eq!(stats.weakest(5).len(), 3);
+    }
+
+    #[test]
+    fn test_letter_confidence() {
+        let mut confidence = LetterConfidence::default();
+        confidence.update(&KeyStats::from_keystrokes(&[
+            key(0, 'a', 'a'),
+            key(100, 'b', 'v'),
+            key(200, 'b', 'b'),
+            key(300, ' ', ' '),
+            key(1000, 'c', 'c'),
+        ]));
+
+        assert_eq!(confidence.get('a'), Some(1.));
+        assert_eq!(confidence.get('b'), Some(0.5));
+        // 700ms is 2.8 times slower than the mean of 250ms
+        assert_eq!(confidence.get('c'), Some(250. / 700.));
+        assert!(confidence.get(' ').is_none());
+        assert_eq!(confidence.weakest(2), vec!['c', 'b']);
+
+        // a clean press pulls the letter up a step
+        confidence.update(&KeyStats::from_keystrokes(&[key(0, 'b', 'b')]));
+        assert!((confidence.get('b').unwrap() - 0.55).abs() < 1e-9);
+    }
+}
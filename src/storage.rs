@@ -1,4 +1,4 @@
-use crate::settings::SCRIPT_SIGN;
+use crate::settings::{CODE_SIGN, QUOTE_SIGN, SCRIPT_SIGN};
 use directories_next::ProjectDirs;
 use lazy_static::lazy_static;
 use std::path::PathBuf;
@@ -19,6 +19,14 @@ pub fn get_word_list_path(word_list_name: &str) -> PathBuf {
     get_storage_dir().join("words").join(word_list_name)
 }
 
+pub fn get_quotes_path(collection_name: &str) -> PathBuf {
+    get_storage_dir().join("quotes").join(collection_name)
+}
+
+pub fn get_code_path(script_name: &str) -> PathBuf {
+    get_storage_dir().join("code").join(script_name)
+}
+
 // this may be lazy_static later or something
 pub fn get_config_file() -> PathBuf {
     get_project_dirs()
@@ -36,7 +44,7 @@ pub fn parse_storage_contents() -> Vec<String> {
             i.unwrap()
                 .path()
                 .iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .to_string_lossy()
                 .to_string()
@@ -51,7 +59,7 @@ pub fn parse_storage_contents() -> Vec<String> {
             i.unwrap()
                 .path()
                 .iter()
-                .last()
+                .next_back()
                 .unwrap()
                 .to_string_lossy()
                 .to_string()
@@ -59,5 +67,29 @@ pub fn parse_storage_contents() -> Vec<String> {
         .map(|s| format!("{}{}", SCRIPT_SIGN, s));
 
     words_list.extend(scripts_iterator);
+
+    // storage installed by older versions may lack the newer dirs
+    words_list.extend(list_signed_dir("quotes", QUOTE_SIGN));
+    words_list.extend(list_signed_dir("code", CODE_SIGN));
+
     words_list
 }
+
+/// lists the files of a storage dir prefixed with the sign of their test variant
+fn list_signed_dir(dir: &str, sign: &str) -> Vec<String> {
+    match get_storage_dir().join(dir).read_dir() {
+        Ok(entries) => entries
+            .map(|i| {
+                i.unwrap()
+                    .path()
+                    .iter()
+                    .next_back()
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .map(|s| format!("{}{}", sign, s))
+            .collect(),
+        Err(_) => vec![],
+    }
+}
@@ -1,7 +1,7 @@
 use crate::colorscheme::ToForeground;
 use crate::langs;
 use crate::settings::TestSummary;
-use crate::settings::TypingTestConfig;
+use crate::settings::{BackspacePolicy, ErrorPolicy, FailPolicy, IdlePolicy, TypingTestConfig};
 use std::time::{Duration, Instant};
 use tui::{style::Color, text::Span};
 
@@ -11,6 +11,58 @@ use tui::{style::Color, text::Span};
 /// seems more than fair
 const INITAL_OFFSET: Duration = Duration::from_millis(50);
 const MAX_EXTRA_MISTAKES: usize = 3;
+/// minimum accuracy isn't enforced for the first few key presses
+/// a single early slip would fail the test otherwise
+const MIN_ACC_GRACE: usize = 10;
+
+/// code tests show line breaks and tabs with these
+/// the key handler translates Enter and Tab accordingly
+pub const NEWLINE: char = '↵';
+pub const TAB: char = '⇥';
+
+/// (ms since the start, chars typed) after every keystroke
+/// chars count every position the caret went through, mistakes included
+pub type Timeline = Vec<(u32, u32)>;
+
+/// logged in place of the typed char for Backspace and Ctrl + Backspace
+pub const BACKSPACE: char = '\u{8}';
+pub const DELETE_WORD: char = '\u{17}';
+
+/// where the test takes the time from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Clock {
+    System,
+    // stands still until moved on, timing tests use it to press keys exactly when they want
+    Manual(Instant),
+}
+
+impl Clock {
+    pub fn now(&self) -> Instant {
+        match self {
+            Self::System => Instant::now(),
+            Self::Manual(at) => *at,
+        }
+    }
+
+    /// moves the manual clock on, the system one can't be moved
+    pub fn advance(&mut self, by: Duration) {
+        if let Self::Manual(at) = self {
+            *at += by;
+        }
+    }
+}
+
+/// a single key press of the test
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Keystroke {
+    /// ms since the start of the test
+    pub ms: u32,
+    /// char under the caret when the key was pressed
+    pub expected: char,
+    pub typed: char,
+    /// backspaced chars and deleted words
+    pub correction: bool,
+}
 
 pub struct WpmHoarder {
     pub wpms: Vec<f64>,
@@ -32,10 +84,19 @@ impl WpmHoarder {
         self.seconds = 1;
     }
 
-    fn is_due(&mut self, begining: Instant) -> bool {
-        let elapsed = begining.elapsed().as_secs();
-        let due_time = self.seconds * (self.wpms.len() as u64 + 1);
-        elapsed >= due_time
+    /// point of the run the next sample is taken at
+    fn next_due(&self) -> Duration {
+        Duration::from_secs(self.seconds * (self.wpms.len() as u64 + 1))
+    }
+
+    fn is_due(&self, elapsed: Duration) -> bool {
+        elapsed >= self.next_due()
+    }
+
+    /// forgets the samples taken after this point of the run
+    fn rewind(&mut self, elapsed: Duration) {
+        let kept = elapsed.as_secs() / self.seconds;
+        self.wpms.truncate(kept as usize);
     }
 
     fn push(&mut self, wpm: f64) {
@@ -61,6 +122,25 @@ impl WpmHoarder {
         max
     }
 
+    /// 100 for perfectly even wpm, goes down as it varies
+    pub fn consistency(&self) -> f64 {
+        if self.wpms.len() < 2 {
+            return 100.;
+        }
+        let n = self.wpms.len() as f64;
+        let mean = self.wpms.iter().sum::<f64>() / n;
+        if mean <= 0. {
+            return 0.;
+        }
+        let variance = self
+            .wpms
+            .iter()
+            .map(|wpm| (wpm - mean).powi(2))
+            .sum::<f64>()
+            / n;
+        (100. * (1. - variance.sqrt() / mean)).max(0.)
+    }
+
     pub fn get_min_max_wpm(&self) -> (f64, f64) {
         let mut max: f64 = self.wpms[0];
         let mut min: f64 = self.wpms[0];
@@ -76,6 +156,19 @@ impl WpmHoarder {
     }
 }
 
+/// how long a word took and how many wrong keys were pressed on the way
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordStat {
+    pub word: String,
+    pub ms: u32,
+    pub misses: usize,
+    /// chars typed before the word was done, tells retyped words apart
+    pub at: usize,
+    /// ms since the start when the word was done
+    pub end: u32,
+}
+
+#[derive(Clone, Copy)]
 pub struct TestColors {
     pub wrong: Color,
     pub todo: Color,
@@ -115,13 +208,52 @@ pub struct TestState<'a> {
     pub cursor_x: u16,
     pub current_char: char,
 
+    // leading whitespace of the active line, code tests only
+    // it is typed like any other char or skipped altogether
+    pub indent: usize,
+    pub skip_indent: bool,
+
+    pub error_policy: ErrorPolicy,
+    pub backspace: BackspacePolicy,
+    pub fail_policy: FailPolicy,
+    pub min_acc: f64,
+    pub failed: bool,
+
+    // the pace caret moves through the text at this speed
+    pub pace_wpm: Option<f64>,
+    pub keystrokes: Vec<Keystroke>,
+    pub words: Vec<WordStat>,
+    // (ms, pmiss) when the word being typed was started
+    word_start: (u32, usize),
+    // timeline of the run raced as a ghost, empty if there is none
+    pub ghost: Timeline,
+    // caret positions on the lines above the active one
+    pub caret_base: usize,
+
     pub first: bool,
     pub begining: Instant,
+    pub clock: Clock,
+    // when the key being handled was read, None in between key events
+    pub stamp: Option<Instant>,
+    // timed tests end once this much time has passed
+    pub time_limit: Option<Duration>,
+    // the clock is stopped since then, None while it runs
+    pub paused_at: Option<Instant>,
+    // (ms since the start, ms it lasted) of every finished pause
+    pub pauses: Vec<(u32, u32)>,
+    last_input: Instant,
+    pub idle_policy: IdlePolicy,
+    // no key press for this long counts as idle
+    pub idle_after: Duration,
     // source for generating test
     pub source: String,
 
     pub text: Vec<Span<'a>>,
     pub length: usize,
+    // every line of the test as it was generated, so it can be retried
+    pub pristine: Vec<Vec<Span<'a>>>,
+    // how many times in a row the same text has been retried
+    pub retries: u32,
 
     pub hoarder: WpmHoarder,
 
@@ -137,7 +269,17 @@ impl Default for TestState<'_> {
             backburner: vec![vec![]],
 
             text: vec![],
+            pristine: vec![],
+            retries: 0,
             begining: Instant::now(),
+            clock: Clock::System,
+            stamp: None,
+            time_limit: None,
+            paused_at: None,
+            pauses: vec![],
+            last_input: Instant::now(),
+            idle_policy: IdlePolicy::Pause,
+            idle_after: Duration::from_secs(10),
 
             // characters done on current line
             // this variable is reset after each line
@@ -156,6 +298,19 @@ impl Default for TestState<'_> {
             pmiss: 0,
 
             cursor_x: 0,
+            indent: 0,
+            skip_indent: true,
+            error_policy: ErrorPolicy::Free,
+            backspace: BackspacePolicy::On,
+            fail_policy: FailPolicy::Off,
+            min_acc: 0.,
+            failed: false,
+            pace_wpm: None,
+            keystrokes: vec![],
+            words: vec![],
+            word_start: (0, 0),
+            ghost: vec![],
+            caret_base: 0,
 
             source: "storage/words/english".to_string(),
             length: 0,
@@ -166,7 +321,7 @@ impl Default for TestState<'_> {
     }
 }
 
-impl TestState<'_> {
+impl<'a> TestState<'a> {
     pub fn with_colors(colors: TestColors) -> Self {
         Self {
             colors,
@@ -175,9 +330,126 @@ impl TestState<'_> {
     }
 
     pub fn calculate_wpm(&self) -> f64 {
+        self.wpm_after(self.elapsed())
+    }
+
+    /// wpm of the chars typed so far if it took this long to type them
+    pub fn wpm_after(&self, elapsed: Duration) -> f64 {
         let numerator: f64 = 12. * (self.pdone + self.done - self.blanks - self.mistakes) as f64;
-        let elapsed = Instant::now().duration_since(self.begining).as_secs_f64();
-        numerator / elapsed
+        numerator / elapsed.as_secs_f64()
+    }
+
+    /// time since the test started, pauses left out
+    /// timed tests never go past their limit
+    fn elapsed(&self) -> Duration {
+        let elapsed = self.wall_clock().saturating_sub(self.paused_time());
+        match self.time_limit {
+            Some(limit) => elapsed.min(limit),
+            None => elapsed,
+        }
+    }
+
+    /// the moment the key being handled was read at
+    /// or the current time in between key events
+    pub fn now(&self) -> Instant {
+        self.stamp.unwrap_or_else(|| self.clock.now())
+    }
+
+    /// time since the test started, pauses included
+    fn wall_clock(&self) -> Duration {
+        self.now().duration_since(self.begining)
+    }
+
+    /// how long the clock has been stopped for, the ongoing pause included
+    fn paused_time(&self) -> Duration {
+        let finished: u32 = self.pauses.iter().map(|&(_, ms)| ms).sum();
+        let ongoing = self
+            .paused_at
+            .map_or(Duration::ZERO, |at| self.now().duration_since(at));
+        Duration::from_millis(finished as u64) + ongoing
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// stops the clock, tests that haven't started have nothing to stop
+    pub fn pause(&mut self) {
+        self.pause_since(self.now());
+    }
+
+    /// stops the clock as of the given moment
+    /// wpm samples taken since then are dropped
+    fn pause_since(&mut self, at: Instant) {
+        if self.first || self.is_paused() {
+            return;
+        }
+        self.paused_at = Some(at);
+        self.hoarder.rewind(self.elapsed());
+    }
+
+    /// starts the clock again and notes down the pause
+    pub fn resume(&mut self) {
+        if let Some(at) = self.paused_at {
+            let ms = self.elapsed().as_millis() as u32;
+            let lasted = self.now().duration_since(at).as_millis() as u32;
+            self.pauses.push((ms, lasted));
+            self.paused_at = None;
+        }
+    }
+
+    /// started tests that haven't seen a key press in a while
+    pub fn is_idle(&self) -> bool {
+        !self.first
+            && !self.is_paused()
+            && self.idle_policy != IdlePolicy::Off
+            && self.now().duration_since(self.last_input) >= self.idle_after
+    }
+
+    /// how long until the test counts as idle
+    pub fn idle_in(&self) -> Option<Duration> {
+        if self.first || self.is_paused() || self.idle_policy == IdlePolicy::Off {
+            return None;
+        }
+        let idle_for = self.now().duration_since(self.last_input);
+        Some(self.idle_after.saturating_sub(idle_for))
+    }
+
+    /// idle time doesn't count, the clock stops at the last key press
+    pub fn pause_idle(&mut self) {
+        self.pause_since(self.last_input);
+    }
+
+    /// every key press resumes a paused test
+    pub fn wake(&mut self) {
+        self.last_input = self.now();
+        self.resume();
+    }
+
+    /// returns None for tests that aren't timed or haven't started yet
+    pub fn time_left(&self) -> Option<Duration> {
+        if self.first {
+            return None;
+        }
+        self.time_limit
+            .map(|limit| limit.saturating_sub(self.elapsed()))
+    }
+
+    /// timed tests run out of words unless fed from time to time
+    pub fn is_starving(&self) -> bool {
+        self.time_limit.is_some() && self.backburner.is_empty()
+    }
+
+    /// appends freshly generated lines at the end of the test
+    pub fn refill(&mut self, config: &TypingTestConfig) {
+        let mut wordy = langs::prepare_test(config, &self.colors);
+        self.pristine.splice(0..0, wordy.iter().cloned());
+        wordy.append(&mut self.backburner);
+        self.backburner = wordy;
+
+        if self.down.is_empty() {
+            self.down = self.backburner.pop().unwrap_or_default();
+        }
     }
 
     fn calculate_acc(&self) -> f64 {
@@ -187,15 +459,49 @@ impl TestState<'_> {
     }
 
     pub fn summarize(&self) -> TestSummary {
+        let elapsed = self.elapsed();
+        let correct_chars = self.pdone + self.done - self.blanks - self.mistakes;
+        let mistakes = self.mistakes + self.extra_mistakes;
+        let typed = self.keystrokes.iter().filter(|key| !key.correction).count();
+
         TestSummary {
-            correct_chars: self.pdone + self.done - self.blanks - self.mistakes,
-            mistakes: self.mistakes + self.extra_mistakes,
-            wpm: self.calculate_wpm(),
+            correct_chars,
+            mistakes,
+            corrected: self.pmiss.saturating_sub(mistakes),
+            wpm: self.wpm_after(elapsed),
+            raw_wpm: 12. * typed as f64 / elapsed.as_secs_f64(),
+            cpm: 60. * correct_chars as f64 / elapsed.as_secs_f64(),
+            consistency: self.hoarder.consistency(),
+            paused: self.paused_time().as_secs_f64(),
+            wall_wpm: self.wpm_after(elapsed + self.paused_time()),
             acc: self.calculate_acc(),
+            failed: self.failed,
+            retries: self.retries,
         }
     }
 
+    /// starts a test on freshly generated text
     pub fn reset(&mut self, config: &TypingTestConfig) {
+        self.pristine = langs::prepare_test(config, &self.colors);
+        self.retries = 0;
+        self.restart(config);
+    }
+
+    /// starts the test over on the very same text
+    pub fn retry(&mut self, config: &TypingTestConfig) {
+        self.retries += 1;
+        self.restart(config);
+    }
+
+    /// starts a test on lines that were generated before
+    pub fn load(&mut self, config: &TypingTestConfig, lines: Vec<Vec<Span<'a>>>) {
+        self.pristine = lines;
+        self.retries = 0;
+        self.restart(config);
+    }
+
+    fn restart(&mut self, config: &TypingTestConfig) {
+        self.cursor_x = 1;
         self.blanks = 0;
         self.done = 0;
         self.pdone = 0;
@@ -205,21 +511,72 @@ impl TestState<'_> {
         self.extra_mistakes = 0;
         self.hoarder.reset();
 
-        let mut wordy = langs::prepare_test(config, &self.colors);
+        let mut wordy = self.pristine.clone();
         self.active = wordy.pop().expect("prep_test output shouldn't be empty");
         self.length = self.active.len();
         self.down = wordy.pop().unwrap_or_default();
         self.backburner = wordy;
-        self.current_char = self.active[self.done].content.chars().next().unwrap();
         self.length = self.active.len();
+        self.skip_indent = config.skip_indent;
+        self.error_policy = config.error_policy;
+        self.backspace = config.backspace;
+        self.fail_policy = config.fail_policy;
+        self.min_acc = config.min_acc as f64;
+        self.failed = false;
+        self.caret_base = 0;
+        self.keystrokes.clear();
+        self.words.clear();
+        self.word_start = (0, 0);
+        self.enter_line();
         self.first = true;
-        self.begining = Instant::now();
+        self.begining = self.now();
+        self.time_limit = config.time_limit();
+        self.paused_at = None;
+        self.pauses.clear();
+        self.idle_policy = config.idle_policy;
+        self.idle_after = Duration::from_secs(config.idle_secs);
+    }
+
+    /// sets up the freshly activated line
+    /// skipping its indentation if need be
+    fn enter_line(&mut self) {
+        self.indent = count_indent(&self.active);
+        if self.skip_indent {
+            self.done += self.indent;
+            self.blanks += self.indent;
+            self.cursor_x += self.indent as u16;
+        }
+        self.set_next_char();
+    }
+
+    /// backspacing can't go past this point on the active line
+    fn line_floor(&self) -> usize {
+        if self.skip_indent {
+            self.indent
+        } else {
+            0
+        }
+    }
+
+    /// takes the wpm samples that came due since the last call
+    /// each one as of the second it was due, however late the call is
+    pub fn sample_wpm(&mut self) {
+        if self.first || self.is_paused() {
+            return;
+        }
+        let elapsed = self.elapsed();
+        while self.hoarder.is_due(elapsed) {
+            let due = self.hoarder.next_due();
+            self.hoarder.push(self.wpm_after(due));
+        }
     }
 
-    pub fn update_wpm_history(&mut self) {
-        if self.hoarder.is_due(self.begining) {
-            self.hoarder.push(self.calculate_wpm());
+    /// how long until the next wpm sample is due
+    pub fn sample_in(&self) -> Option<Duration> {
+        if self.first || self.is_paused() {
+            return None;
         }
+        Some(self.hoarder.next_due().saturating_sub(self.elapsed()))
     }
 
     /// chekcs if char is a mistake and deducts it from
@@ -263,6 +620,7 @@ impl TestState<'_> {
     fn progress_line(&mut self) -> bool {
         self.up.clear();
         self.up.append(&mut self.active);
+        self.caret_base += caret_positions(&self.up, self.skip_indent).len();
         if self.down.is_empty() {
             return true;
         }
@@ -279,16 +637,20 @@ impl TestState<'_> {
 
         self.cursor_x = 1;
         self.length = self.active.len();
-        self.set_next_char();
+        self.enter_line();
         false
     }
 
     fn regress_line(&mut self) {
+        // skipped indentation of the line left behind isn't done anymore
+        self.blanks -= self.line_floor();
+
         let mut temp: Vec<Span> = vec![];
         temp.append(&mut self.down);
         self.backburner.push(temp);
 
         self.down.append(&mut self.active);
+        self.caret_base -= caret_positions(&self.up, self.skip_indent).len();
         self.active.append(&mut self.up);
 
         self.length = self.active.len();
@@ -298,10 +660,11 @@ impl TestState<'_> {
 
         let mut crs = 0;
         for sp in &self.active {
-            crs += sp.content.len();
+            crs += sp.content.chars().count();
         }
 
         self.cursor_x = 1 + crs as u16;
+        self.indent = count_indent(&self.active);
     }
 
     fn set_next_char_or_end(&mut self) -> bool {
@@ -316,8 +679,36 @@ impl TestState<'_> {
     /// handles char event and returns
     /// returns a boolean signaling status of the test
     /// returns false when the test continues
-    /// returns true when the test is done
+    /// returns true when the test is done or failed
     pub fn on_char(&mut self, c: char) -> bool {
+        // keys pressed after the deadline don't count
+        if self.time_left() == Some(Duration::ZERO) {
+            return true;
+        }
+
+        let expected = self.current_char;
+        if self.fail_policy == FailPolicy::SuddenDeath && self.leaves_mistake_behind(c) {
+            self.failed = true;
+            self.log_key(expected, c);
+            return true;
+        }
+
+        let is_done = self.type_char(c);
+        self.log_key(expected, c);
+
+        if self.fail_policy == FailPolicy::MinAccuracy && self.is_below_min_acc() {
+            self.failed = true;
+            return true;
+        }
+        is_done
+    }
+
+    fn is_below_min_acc(&self) -> bool {
+        let key_presses = self.pdone + self.done - self.blanks - self.mistakes + self.pmiss;
+        key_presses >= MIN_ACC_GRACE && self.calculate_acc() < self.min_acc
+    }
+
+    fn type_char(&mut self, c: char) -> bool {
         self.cursor_x += 1;
 
         // TODO this implemenation is quick and dirty
@@ -332,10 +723,21 @@ impl TestState<'_> {
             // instead he gets it on the above world record pace of 240wpm
             // which isn't at all significant for the player
             // but helps the software
-            self.begining = Instant::now().checked_sub(INITAL_OFFSET).unwrap();
+            self.begining = self.now().checked_sub(INITAL_OFFSET).unwrap();
+        }
+
+        if self.refuses(c) {
+            // cursor is pushed +1 when KeyCode::Char is matched
+            self.cursor_x -= 1;
+            if c != self.current_char {
+                self.pmiss += 1;
+                self.active[self.done].style = self.colors.wrong.fg();
+            }
+            return false;
         }
 
         if c == self.current_char {
+            self.note_word();
             self.active[self.done].style = self.colors.done.fg();
             self.done += 1;
             return self.set_next_char_or_end();
@@ -343,7 +745,7 @@ impl TestState<'_> {
 
         // wrong key
         // adds the mistake and the end of the word
-        if self.current_char == ' ' {
+        if self.current_char == ' ' && self.done > self.indent {
             self.pmiss += 1;
             if self.fetch(self.done - 1).len() < MAX_EXTRA_MISTAKES {
                 self.extra_mistakes += 1;
@@ -361,6 +763,7 @@ impl TestState<'_> {
         } else {
             self.mistakes += 1;
             self.pmiss += 1;
+            self.note_word();
             self.active[self.done].style = self.colors.wrong.fg();
             self.done += 1;
             return self.set_next_char_or_end();
@@ -368,8 +771,185 @@ impl TestState<'_> {
         false
     }
 
+    /// words are noted down once the char following them is typed
+    /// the last one of the test as soon as its last char is
+    fn note_word(&mut self) {
+        let ends_test = self.done + 1 == self.length && self.down.is_empty();
+        let end = match self.current_char {
+            // indentation isn't a word
+            ' ' if self.done <= self.indent => return,
+            // the blank holding extras isn't part of the word
+            ' ' => self.done - 1,
+            NEWLINE => self.done,
+            _ if ends_test => self.done + 1,
+            _ => return,
+        };
+
+        let start = self.active[..end]
+            .iter()
+            .rposition(|span| span.content == " " || span.content == TAB.to_string())
+            .map_or(0, |i| i + 1);
+        let word: String = self.active[start..end]
+            .iter()
+            .map(|span| span.content.as_ref())
+            .collect();
+
+        let ms = self.elapsed().as_millis() as u32;
+        if !word.is_empty() {
+            let at = self.typed_chars();
+            // words that were backspaced into are typed again
+            self.words.retain(|stat| stat.at < at);
+            self.words.push(WordStat {
+                word,
+                ms: ms.saturating_sub(self.word_start.0),
+                misses: self.pmiss - self.word_start.1,
+                at,
+                end: ms,
+            });
+        }
+        self.word_start = (ms, self.pmiss);
+    }
+
+    /// strict error policies don't let the test move on
+    /// the refused wrong key is shown on the char it was aimed at
+    fn refuses(&self, c: char) -> bool {
+        match self.error_policy {
+            ErrorPolicy::Free => false,
+            ErrorPolicy::StopOnLetter => c != self.current_char,
+            ErrorPolicy::StopOnWord => self.leaves_mistake_behind(c),
+        }
+    }
+
+    /// checks whether the key would finish a word that has mistakes in it
+    fn leaves_mistake_behind(&self, c: char) -> bool {
+        let at_word_end = self.current_char == ' ' || self.current_char == NEWLINE;
+        let ends_test = self.done + 1 == self.length && self.down.is_empty();
+
+        if at_word_end && self.done > self.indent {
+            c == self.current_char && !self.word_is_clean()
+        } else if ends_test {
+            c != self.current_char || !self.word_is_clean()
+        } else {
+            false
+        }
+    }
+
+    /// checks the word right before the cursor for mistakes and extras
+    fn word_is_clean(&self) -> bool {
+        let mut end = self.done;
+        // the blank holding extras sits right before the space
+        if self.current_char == ' ' {
+            if !self.fetch(self.done - 1).is_empty() {
+                return false;
+            }
+            end -= 1;
+        }
+
+        self.active[self.line_floor()..end]
+            .iter()
+            .rev()
+            .take_while(|span| span.content != " ")
+            .all(|span| span.style.fg != Some(self.colors.wrong))
+    }
+
+    /// wipes the wrong color left by a refused key
+    fn clear_refusal(&mut self) {
+        if self.error_policy != ErrorPolicy::Free && self.done < self.active.len() {
+            self.active[self.done].style = self.colors.todo.fg();
+        }
+    }
+
+    /// chars the pace caret has gone through so far
+    pub fn pace_chars(&self) -> Option<usize> {
+        let wpm = self.pace_wpm?;
+        if self.first {
+            return Some(0);
+        }
+        Some((wpm * self.elapsed().as_secs_f64() / 12.) as usize)
+    }
+
+    /// chars the ghost had gone through at this point of its run
+    pub fn ghost_chars(&self) -> Option<usize> {
+        if self.ghost.is_empty() {
+            return None;
+        }
+        if self.first {
+            return Some(0);
+        }
+
+        let ms = self.elapsed().as_millis() as u32;
+        let passed = self.ghost.partition_point(|&(at, _)| at <= ms);
+        Some(
+            passed
+                .checked_sub(1)
+                .map_or(0, |i| self.ghost[i].1 as usize),
+        )
+    }
+
+    /// chars ahead of the ghost, negative if behind
+    pub fn ghost_gap(&self) -> Option<i64> {
+        Some(self.typed_chars() as i64 - self.ghost_chars()? as i64)
+    }
+
+    /// positions the caret went through, mistakes included
+    pub fn typed_chars(&self) -> usize {
+        self.pdone + self.done - self.blanks
+    }
+
+    /// keys pressed before the test starts aren't logged
+    fn log_key(&mut self, expected: char, typed: char) {
+        if self.first {
+            return;
+        }
+        self.keystrokes.push(Keystroke {
+            ms: self.elapsed().as_millis() as u32,
+            expected,
+            typed,
+            correction: typed == BACKSPACE || typed == DELETE_WORD,
+        });
+    }
+
+    /// correct chars ahead of the pace caret, negative if behind
+    pub fn pace_gap(&self) -> Option<i64> {
+        let correct = self.pdone + self.done - self.blanks - self.mistakes;
+        Some(correct as i64 - self.pace_chars()? as i64)
+    }
+
+    /// finds the span a caret that went through this many chars sits on
+    /// returns the index of the displayed line (up, active, down) and of the span
+    /// None if the caret is out of sight
+    pub fn locate_caret(&self, chars: usize) -> Option<(usize, usize)> {
+        if chars < self.caret_base {
+            let up = caret_positions(&self.up, self.skip_indent);
+            let back = self.caret_base - chars;
+            return up.len().checked_sub(back).map(|i| (0, up[i]));
+        }
+
+        let mut offset = chars - self.caret_base;
+        for (row, line) in [(1, &self.active), (2, &self.down)] {
+            let positions = caret_positions(line, self.skip_indent);
+            if offset < positions.len() {
+                return Some((row, positions[offset]));
+            }
+            offset -= positions.len();
+        }
+        None
+    }
+
     // BACKSPACE undo_char, undo_word
 
+    /// checks whether the backspace policy lets anything be taken back
+    pub fn can_undo(&self) -> bool {
+        match self.backspace {
+            BackspacePolicy::On => true,
+            BackspacePolicy::Off => false,
+            // finished words are behind a typed space or another line
+            BackspacePolicy::WithinWord => {
+                self.done > self.line_floor() && self.fetch(self.done - 1) != " "
+            }
+        }
+    }
+
     // undo word
 
     fn undo_space_char_and_extras(&mut self) {
@@ -385,8 +965,17 @@ impl TestState<'_> {
         self.blanks -= 1;
     }
 
+    fn undo_letter(&mut self) {
+        self.cursor_x -= 1;
+        self.done -= 1;
+        self.if_mistake_deduct(self.done);
+        self.active[self.done].style = self.colors.todo.fg();
+    }
+
     pub fn undo_word(&mut self) {
-        if self.done == 0 {
+        self.log_key(self.current_char, DELETE_WORD);
+        self.clear_refusal();
+        if self.done == self.line_floor() {
             if !self.up.is_empty() {
                 self.regress_line();
             } else {
@@ -394,9 +983,17 @@ impl TestState<'_> {
             }
         }
 
+        // typed indentation goes away as a whole
+        if self.done <= self.indent {
+            while self.done != self.line_floor() {
+                self.undo_letter();
+            }
+            return;
+        }
+
         if self.current_char == ' ' {
             self.undo_space_char_and_extras();
-        } else if self.fetch(self.done - 1) == " " {
+        } else if self.done > self.indent + 1 && self.fetch(self.done - 1) == " " {
             self.done -= 1;
             self.cursor_x -= 1;
             self.active[self.done].style = self.colors.todo.fg();
@@ -404,21 +1001,24 @@ impl TestState<'_> {
             self.undo_space_char_and_extras();
         }
 
-        while self.done != 0 && self.fetch(self.done - 1) != " " {
-            self.cursor_x -= 1;
-            self.done -= 1;
-            self.if_mistake_deduct(self.done);
-            self.active[self.done].style = self.colors.todo.fg();
+        while self.done != self.indent && self.fetch(self.done - 1) != " " {
+            self.undo_letter();
         }
     }
 
+    pub fn undo_char(&mut self) {
+        self.log_key(self.current_char, BACKSPACE);
+        self.take_back_char();
+    }
+
     // undo char
     //
-    pub fn undo_char(&mut self) {
-        if self.done > 0 {
+    fn take_back_char(&mut self) {
+        self.clear_refusal();
+        if self.done > self.line_floor() {
             self.cursor_x -= 1;
 
-            if self.current_char == ' ' {
+            if self.current_char == ' ' && self.done > self.indent {
                 if self.active[self.done - 1].content.is_empty() {
                     self.if_mistake_deduct(self.done - 2);
                     self.done -= 2;
@@ -446,18 +1046,37 @@ impl TestState<'_> {
 
         if !self.up.is_empty() {
             self.regress_line();
-            self.undo_char();
+            self.take_back_char();
         }
     }
 }
 
+/// indexes of the spans a caret can sit on
+/// blanks and skipped indentation are left out
+fn caret_positions(line: &[Span], skip_indent: bool) -> Vec<usize> {
+    let indent = count_indent(line);
+    let start = if skip_indent { indent } else { 0 };
+
+    // past the indentation only blanks are followed by a space
+    (start..line.len())
+        .filter(|&i| i < indent || !matches!(line.get(i + 1), Some(next) if next.content == " "))
+        .collect()
+}
+
+/// amount of whitespace spans the line starts with
+fn count_indent(line: &[Span]) -> usize {
+    line.iter()
+        .take_while(|span| span.content == " " || span.content == TAB.to_string())
+        .count()
+}
+
 // TODO these tests save to real database XEDDD
 // plx fix
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::application::App;
-    use crate::settings::TypingTestConfig;
+    use crate::settings::{TestVariant, TypingTestConfig};
 
     fn get_wrong_char(c: char) -> char {
         if c == 'ź' {
@@ -478,6 +1097,312 @@ mod tests {
         test
     }
 
+    fn setup_code_test(code: &str, skip_indent: bool) -> TestState<'static> {
+        let mut test = TestState::default();
+        let mut lines = langs::to_code_span(code, &test.colors);
+        test.active = lines.pop().unwrap();
+        test.length = test.active.len();
+        test.down = lines.pop().unwrap_or_default();
+        test.backburner = lines;
+        test.skip_indent = skip_indent;
+        test.cursor_x = 1;
+        test.enter_line();
+        test
+    }
+
+    #[test]
+    fn test_code_indent() {
+        let code = "{\n    a b\n}";
+
+        // skipped indentation is counted as blanks and can't be backspaced
+        let mut test = setup_code_test(code, true);
+        test.on_char('{');
+        assert_eq!(test.current_char, NEWLINE);
+        test.on_char(NEWLINE);
+        assert_eq!((test.done, test.blanks), (4, 4));
+        assert_eq!(test.current_char, 'a');
+        test.undo_char();
+        assert_eq!((test.pdone, test.done, test.blanks), (0, 1, 0));
+        assert_eq!(test.current_char, NEWLINE);
+        test.on_char(NEWLINE);
+        assert_eq!((test.done, test.blanks), (4, 4));
+        test.on_char('a');
+        test.undo_word();
+        test.set_next_char();
+        assert_eq!((test.done, test.blanks), (4, 4));
+        assert_eq!(test.current_char, 'a');
+        for c in "a b".chars() {
+            test.on_char(c);
+        }
+        test.on_char(NEWLINE);
+        assert!(test.on_char('}'));
+        assert_eq!(test.summarize().correct_chars, 7);
+
+        // typed indentation is a part of the test
+        let mut test = setup_code_test(code, false);
+        for c in "{\n".chars() {
+            test.on_char(if c == '\n' { NEWLINE } else { c });
+        }
+        assert_eq!((test.done, test.current_char), (0, ' '));
+        test.on_char('x');
+        assert_eq!((test.done, test.mistakes), (1, 1));
+        test.on_char(' ');
+        test.undo_word();
+        test.set_next_char();
+        assert_eq!((test.done, test.mistakes), (0, 0));
+    }
+
+    #[test]
+    fn test_word_stats() {
+        let mut test = setup_code_test("ab cd ef", true);
+        for c in "ab c".chars() {
+            test.on_char(c);
+        }
+        // backspacing into a finished word means it's typed again
+        test.undo_char();
+        test.undo_char();
+        test.undo_char();
+        for c in "b cxd e".chars() {
+            test.on_char(c);
+        }
+        assert!(test.on_char('f'));
+
+        let words: Vec<(&str, usize)> = test
+            .words
+            .iter()
+            .map(|stat| (stat.word.as_str(), stat.misses))
+            .collect();
+        assert_eq!(words, vec![("ab", 0), ("cd", 2), ("ef", 0)]);
+    }
+
+    #[test]
+    fn test_summary_stats() {
+        let mut test = setup_code_test("ab cd", true);
+        test.on_char('a');
+        test.on_char('x');
+        test.undo_char();
+        for c in "b c".chars() {
+            test.on_char(c);
+        }
+        assert!(test.on_char('x'));
+
+        let summary = test.summarize();
+        assert_eq!((summary.mistakes, summary.corrected), (1, 1));
+        assert_eq!(summary.correct_chars, 4);
+        // 6 chars typed in the time 4 correct ones took
+        assert!((summary.raw_wpm / summary.wpm - 1.5).abs() < 1e-9);
+        assert!((summary.cpm / summary.wpm - 5.).abs() < 1e-9);
+
+        test.hoarder.wpms = vec![50., 50.];
+        assert_eq!(test.hoarder.consistency(), 100.);
+        test.hoarder.wpms = vec![40., 60.];
+        assert_eq!(test.hoarder.consistency(), 80.);
+    }
+
+    #[test]
+    fn test_stop_on_letter() {
+        let mut test = setup_new_test();
+        test.error_policy = ErrorPolicy::StopOnLetter;
+
+        let c = test.current_char;
+        test.on_char(get_wrong_char(c));
+        assert_eq!((test.done, test.mistakes, test.pmiss), (0, 0, 1));
+        assert_eq!(test.cursor_x, 1);
+
+        test.on_char(c);
+        assert_eq!(test.done, 1);
+
+        // no extras either
+        while test.current_char != ' ' {
+            test.on_char(test.current_char);
+        }
+        let done = test.done;
+        test.on_char(get_wrong_char(' '));
+        assert_eq!(test.done, done);
+        assert!(test.fetch(done - 1).is_empty());
+        assert_eq!(test.summarize().mistakes, 0);
+    }
+
+    #[test]
+    fn test_stop_on_word() {
+        let mut test = setup_new_test();
+        test.error_policy = ErrorPolicy::StopOnWord;
+
+        // a wrong letter is let through
+        test.on_char(get_wrong_char(test.current_char));
+        assert_eq!((test.done, test.mistakes), (1, 1));
+        while test.current_char != ' ' {
+            test.on_char(test.current_char);
+        }
+
+        // but the space isn't until it's fixed
+        let done = test.done;
+        test.on_char(' ');
+        assert_eq!(test.done, done);
+        assert_eq!(test.current_char, ' ');
+
+        test.undo_word();
+        test.set_next_char();
+        assert_eq!((test.done, test.mistakes), (0, 0));
+        while test.current_char != ' ' {
+            test.on_char(test.current_char);
+        }
+
+        // extras have to go too
+        test.on_char('x');
+        test.on_char(' ');
+        assert_eq!(test.done, done);
+        test.undo_char();
+        test.on_char(' ');
+        assert_eq!(test.done, done + 1);
+    }
+
+    #[test]
+    fn test_sudden_death() {
+        let mut test = setup_new_test();
+        test.fail_policy = FailPolicy::SuddenDeath;
+
+        // a corrected mistake is forgiven
+        test.on_char(get_wrong_char(test.current_char));
+        test.undo_char();
+        while test.current_char != ' ' {
+            assert!(!test.on_char(test.current_char));
+        }
+        assert!(!test.on_char(' '));
+
+        // the one carried over the space isn't
+        test.on_char(get_wrong_char(test.current_char));
+        while test.current_char != ' ' {
+            test.on_char(test.current_char);
+        }
+        assert!(test.on_char(' '));
+        assert!(test.summarize().failed);
+    }
+
+    #[test]
+    fn test_min_accuracy() {
+        let mut test = setup_new_test();
+        test.fail_policy = FailPolicy::MinAccuracy;
+        test.min_acc = 90.;
+
+        // early slips are let go
+        test.on_char(get_wrong_char(test.current_char));
+        assert!(!test.failed);
+
+        while !test.on_char(test.current_char) {
+            if test.pdone + test.done > 30 {
+                break;
+            }
+        }
+        assert!(!test.failed);
+
+        for _ in 0..5 {
+            test.on_char(get_wrong_char(test.current_char));
+        }
+        assert!(test.failed);
+    }
+
+    #[test]
+    fn test_backspace_within_word() {
+        let mut test = setup_new_test();
+        test.backspace = BackspacePolicy::WithinWord;
+        assert!(!test.can_undo());
+
+        test.on_char(test.current_char);
+        assert!(test.can_undo());
+        while test.current_char != ' ' {
+            test.on_char(test.current_char);
+        }
+        // extras are still a part of the word
+        test.on_char('x');
+        assert!(test.can_undo());
+
+        test.undo_char();
+        test.on_char(' ');
+        assert!(!test.can_undo());
+
+        test.backspace = BackspacePolicy::Off;
+        test.on_char(test.current_char);
+        assert!(!test.can_undo());
+    }
+
+    #[test]
+    fn test_pace_caret() {
+        let mut test = setup_new_test();
+        test.clock = Clock::Manual(Instant::now());
+        assert!(test.pace_chars().is_none());
+
+        test.pace_wpm = Some(60.);
+        assert_eq!(test.pace_chars(), Some(0));
+        assert_eq!(test.locate_caret(0), Some((1, 0)));
+
+        // 60wpm is 5 chars per second
+        test.on_char(test.current_char);
+        test.clock.advance(Duration::from_secs(2));
+        assert_eq!(test.pace_chars(), Some(10));
+        assert_eq!(test.pace_gap(), Some(-9));
+
+        // blanks are stepped over
+        let first_word = test.active.iter().position(|s| s.content == " ").unwrap() - 1;
+        assert_eq!(test.locate_caret(first_word), Some((1, first_word + 1)));
+
+        // the caret follows the test to the next line
+        let active_len = caret_positions(&test.active, true).len();
+        while test.up.is_empty() {
+            test.on_char(test.current_char);
+        }
+        assert_eq!(test.caret_base, active_len);
+        assert_eq!(test.locate_caret(active_len), Some((1, 0)));
+        assert_eq!(test.locate_caret(active_len - 1).map(|c| c.0), Some(0));
+        assert!(test.locate_caret(0).is_some());
+    }
+
+    #[test]
+    fn test_idle_pause() {
+        let mut test = setup_new_test();
+        test.clock = Clock::Manual(Instant::now());
+        test.idle_after = Duration::from_secs(2);
+        test.on_char(test.current_char);
+        test.clock.advance(Duration::from_secs(1));
+        test.wake();
+        test.on_char(test.current_char);
+        assert!(!test.is_idle());
+
+        // 2 seconds without a key press
+        test.clock.advance(Duration::from_secs(2));
+        assert!(test.is_idle());
+        test.pause_idle();
+        assert!(test.is_paused() && !test.is_idle());
+        assert_eq!(test.elapsed(), Duration::from_millis(1050));
+
+        test.wake();
+        assert!(!test.is_paused());
+        assert_eq!(test.pauses, vec![(1050, 2000)]);
+
+        let summary = test.summarize();
+        assert_eq!(summary.paused, 2.);
+        assert_eq!(
+            summary.wall_wpm,
+            test.wpm_after(Duration::from_millis(3050))
+        );
+    }
+
+    #[test]
+    fn test_ghost_race() {
+        let mut test = setup_new_test();
+        test.clock = Clock::Manual(Instant::now());
+        assert!(test.ghost_chars().is_none());
+
+        test.on_char(test.current_char);
+        test.ghost = vec![(100, 1), (1000, 2), (2000, 3)];
+        test.clock.advance(Duration::from_millis(1450));
+        assert_eq!(test.ghost_chars(), Some(2));
+        assert_eq!(test.ghost_gap(), Some(-1));
+
+        test.clock.advance(Duration::from_secs(10));
+        assert_eq!(test.ghost_chars(), Some(3));
+    }
+
     #[test]
     fn test_undo_char() {
         let mut test = setup_new_test();
@@ -506,6 +1431,89 @@ mod tests {
         assert!(test.fetch(done - 1).is_empty());
     }
 
+    #[test]
+    fn test_timed_test_ends_at_deadline() {
+        let config = TypingTestConfig {
+            variant: TestVariant::Timed,
+            duration: 15,
+            ..Default::default()
+        };
+
+        let mut test = TestState::default();
+        test.reset(&config);
+        test.clock = Clock::Manual(Instant::now());
+        assert!(test.time_left().is_none());
+
+        assert!(!test.on_char(test.current_char));
+        assert!(test.time_left().unwrap() > Duration::ZERO);
+
+        test.clock.advance(Duration::from_secs(15));
+        assert_eq!(test.time_left(), Some(Duration::ZERO));
+
+        let done = test.done;
+        assert!(test.on_char(test.current_char));
+        assert_eq!(done, test.done);
+    }
+
+    #[test]
+    fn test_retry_keeps_the_text() {
+        let config = TypingTestConfig {
+            length: 100,
+            ..Default::default()
+        };
+        let mut test = TestState::default();
+        test.reset(&config);
+
+        let text: Vec<String> = test.active.iter().map(|s| s.content.to_string()).collect();
+        for _ in 0..20 {
+            test.on_char(get_wrong_char(test.current_char));
+        }
+        test.hoarder.push(42.);
+
+        test.retry(&config);
+        let retried: Vec<String> = test.active.iter().map(|s| s.content.to_string()).collect();
+        assert_eq!(text, retried);
+        assert_eq!((test.done, test.mistakes, test.pmiss), (0, 0, 0));
+        assert!(test.hoarder.wpms.is_empty());
+        assert_eq!(test.summarize().retries, 1);
+
+        test.reset(&config);
+        assert_eq!(test.summarize().retries, 0);
+    }
+
+    #[test]
+    fn test_keystroke_log() {
+        let mut test = setup_new_test();
+        // nothing is logged before the test starts
+        test.undo_char();
+        assert!(test.keystrokes.is_empty());
+
+        let first = test.current_char;
+        test.on_char(first);
+        let second = test.current_char;
+        test.on_char(get_wrong_char(second));
+        let third = test.current_char;
+        test.undo_char();
+        let fourth = test.current_char;
+        test.undo_word();
+
+        let logged: Vec<(char, char, bool)> = test
+            .keystrokes
+            .iter()
+            .map(|k| (k.expected, k.typed, k.correction))
+            .collect();
+        assert_eq!(
+            logged,
+            vec![
+                (first, first, false),
+                (second, get_wrong_char(second), false),
+                (third, BACKSPACE, true),
+                (fourth, DELETE_WORD, true),
+            ]
+        );
+        assert!(test.keystrokes.windows(2).all(|w| w[0].ms <= w[1].ms));
+    }
+
     #[test]
     fn test_undo_previous_line() {
         let mut test = setup_new_test();
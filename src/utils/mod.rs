@@ -77,6 +77,20 @@ pub fn count_lines_from_path(filepath: impl AsRef<Path>) -> anyhow::Result<usize
     count_lines(file)
 }
 
+/// FNV-1a, unlike the std hashers it stays the same between releases
+/// so it can tell saved tests apart
+///
+/// ```
+/// use smokey::utils::stable_hash;
+/// assert_eq!(stable_hash(""), 0x811c9dc5);
+/// assert_eq!(stable_hash("a"), 0xe40c292c);
+/// ```
+pub fn stable_hash(s: &str) -> u32 {
+    s.bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ byte as u32).wrapping_mul(0x01000193)
+    })
+}
+
 /// Expects a file and returns number of lines
 ///
 /// ```